@@ -26,4 +26,114 @@ pub enum ErrorCode {
     WinnerAlreadySelected,
     #[msg("Lottery still on and winner has not been selected yet")]
     LotteryStillOn,
-}
\ No newline at end of file
+    #[msg("Signer is not allowed to perform this action")]
+    Unauthorized,
+    #[msg("There is no pending admin to accept")]
+    NoPendingAdmin,
+    #[msg("This action is currently paused")]
+    Paused,
+    #[msg("Unknown pause flags")]
+    InvalidPauseFlags,
+    #[msg("Fee must not exceed 10000 bps")]
+    InvalidFeeBps,
+    #[msg("Config allow list is full")]
+    ConfigListFull,
+    #[msg("Lending program is not allowed by the program config")]
+    LendingProgramNotAllowed,
+    #[msg("Reserve is not allowed by the program config")]
+    ReserveNotAllowed,
+    #[msg("Oracle queue is not allowed by the program config")]
+    OracleQueueNotAllowed,
+    #[msg("Treasury token account is not owned by the configured treasury")]
+    InvalidTreasury,
+    #[msg("Timelock delay must not be negative")]
+    InvalidTimelockDelay,
+    #[msg("Queued change can't be executed before its eta")]
+    TimelockNotExpired,
+    #[msg("Queued change does not apply to this account")]
+    InvalidChangeTarget,
+    #[msg("Multisig needs between 1 and 10 signers")]
+    InvalidSigners,
+    #[msg("Threshold must be between 1 and the number of signers")]
+    InvalidThreshold,
+    #[msg("Signer is not part of the multisig")]
+    NotMultisigSigner,
+    #[msg("Proposal has already been executed")]
+    ProposalAlreadyExecuted,
+    #[msg("Multisig signers changed since the proposal was created")]
+    StaleProposal,
+    #[msg("Proposal does not authorize this action")]
+    ProposalActionMismatch,
+    #[msg("Proposal has not reached the approval threshold")]
+    NotEnoughApprovals,
+    #[msg("Account is already on the latest layout")]
+    AlreadyMigrated,
+    #[msg("Lottery still has participants or is not settled/cancelled")]
+    OutstandingClaims,
+    #[msg("Vault still holds tokens")]
+    VaultNotEmpty,
+    #[msg("Account is not the original payer of the lottery")]
+    InvalidPayer,
+    #[msg("Unknown yield strategy")]
+    InvalidStrategy,
+    #[msg("Lottery has no yield strategy")]
+    StrategyDisabled,
+    #[msg("Accounts do not belong to the lottery's yield strategy")]
+    InvalidStrategyAccounts,
+    #[msg("Lending strategy needs the collateral mint, vault and token program")]
+    LendingAccountsRequired,
+    #[msg("Lottery without a yield strategy needs its prize vault to choose a winner")]
+    PrizeVaultRequired,
+    #[msg("Mint does not match the lottery's liquidity mint")]
+    InvalidMint,
+    #[msg("Lottery invests on entry, use enter_lottery_invested")]
+    InvestOnEntry,
+    #[msg("Lottery does not invest on entry")]
+    NotInvestOnEntry,
+    #[msg("Buffer must be between 0 and 10000 bps")]
+    InvalidBufferBps,
+    #[msg("Collateral is not worth more than the invested principal")]
+    NothingToHarvest,
+    #[msg("Allocation slot is out of range or unset")]
+    InvalidAllocation,
+    #[msg("Allocation weights exceed 10000 bps")]
+    InvalidAllocationWeights,
+    #[msg("Allocation still holds collateral")]
+    AllocationNotEmpty,
+    #[msg("Staking accounts do not match the lottery's staking position")]
+    InvalidStakingAccounts,
+    #[msg("Collateral is staked, unstake it first")]
+    CollateralStaked,
+    #[msg("No collateral to stake or unstake")]
+    NothingToStake,
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+    #[msg("Withdrawal queue is full")]
+    WithdrawalQueueFull,
+    #[msg("Withdrawal queue is empty")]
+    WithdrawalQueueEmpty,
+    #[msg("Queued withdrawals have to be paid first")]
+    WithdrawalsQueued,
+    #[msg("VRF client is already registered")]
+    VrfClientAlreadyRegistered,
+    #[msg("No room for another VRF client")]
+    TooManyVrfClients,
+    #[msg("VRF client is not registered for the lottery")]
+    VrfClientNotRegistered,
+    #[msg("Migration needs a value for a field the old layout lacks")]
+    MigrationBackfillRequired,
+    #[msg("VRF result was requested before the lottery locked")]
+    StaleVrfResult,
+    #[msg("VRF client has no result yet")]
+    VrfResultMissing,
+    #[msg("Lottery has no participants")]
+    NoParticipants,
+    #[msg("Reserve or its collateral mint is already used by another allocation")]
+    DuplicateReserve,
+    #[msg("VRF client has a randomness request in flight")]
+    VrfRequestPending,
+    #[msg("Staking program is not allowed by the config")]
+    StakingProgramNotAllowed,
+    #[msg("Lottery without a strategy can't migrate, it has no reserve to move from")]
+    NothingToMigrate,
+}
@@ -0,0 +1,88 @@
+use crate::errors::ErrorCode;
+use crate::{
+    utils::{LOTTERY_INFO_STR, MULTISIG_STR, PROPOSAL_STR},
+    AdminAction, AdminProposal, AdminTransferred, LotteryInfo, Multisig, RoleUpdated,
+};
+use anchor_lang::prelude::*;
+
+pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let lottery_acct = &mut ctx.accounts.lottery_acct;
+    let previous_admin = lottery_acct.admin;
+    lottery_acct.admin = ctx.accounts.pending_admin.key();
+    lottery_acct.pending_admin = None;
+
+    emit!(AdminTransferred {
+        lottery: lottery_acct.key(),
+        previous_admin,
+        admin: lottery_acct.admin,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn set_roles(ctx: Context<UpdateLotteryRoles>, params: SetRolesParams) -> Result<()> {
+    ctx.accounts.proposal.consume(
+        &ctx.accounts.multisig,
+        &AdminAction::SetRoles {
+            operator: params.operator,
+            guardian: params.guardian,
+        },
+    )?;
+    let lottery_acct = &mut ctx.accounts.lottery_acct;
+    if let Some(operator) = params.operator {
+        lottery_acct.operator = operator;
+    }
+    if let Some(guardian) = params.guardian {
+        lottery_acct.guardian = guardian;
+    }
+
+    emit!(RoleUpdated {
+        lottery: lottery_acct.key(),
+        operator: lottery_acct.operator,
+        guardian: lottery_acct.guardian,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SetRolesParams {
+    pub operator: Option<Pubkey>,
+    pub guardian: Option<Pubkey>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateLotteryRoles<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        mut,
+        seeds = [PROPOSAL_STR.as_bytes(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    proposal: Box<Account<'info, AdminProposal>>,
+    #[account(
+        seeds = [MULTISIG_STR.as_bytes(),],
+        bump = multisig.bump,
+    )]
+    multisig: Box<Account<'info, Multisig>>,
+    admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        constraint = lottery_acct.pending_admin.is_some() @ ErrorCode::NoPendingAdmin,
+        constraint = lottery_acct.pending_admin == Some(pending_admin.key()) @ ErrorCode::Unauthorized,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    pending_admin: Signer<'info>,
+}
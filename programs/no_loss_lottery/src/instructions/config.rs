@@ -0,0 +1,156 @@
+use crate::errors::ErrorCode;
+use crate::program::NoLossLottery;
+use crate::{
+    pause_flags,
+    utils::{CONFIG_STR, LOTTERY_INFO_STR, MULTISIG_STR, PROPOSAL_STR},
+    AdminAction, AdminProposal, LotteryInfo, Multisig, PauseFlagsUpdated, ProgramConfig,
+};
+use anchor_lang::prelude::*;
+
+pub fn initialize_config(ctx: Context<InitializeConfig>, params: InitializeConfigParams) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.bump = *ctx.bumps.get("config").unwrap();
+    config.admin = ctx.accounts.admin.key();
+    config.guardian = params.guardian;
+    config.pause_flags = 0;
+    config.treasury = params.treasury;
+    config.set_fee_bps(params.fee_bps)?;
+    config.set_allowed_lending_programs(params.allowed_lending_programs)?;
+    config.set_allowed_reserves(params.allowed_reserves)?;
+    config.set_allowed_oracle_queues(params.allowed_oracle_queues)?;
+    config.set_timelock_delay(params.timelock_delay)?;
+    config.change_count = 0;
+    config.set_allowed_staking_programs(params.allowed_staking_programs)?;
+    Ok(())
+}
+
+pub fn update_config(ctx: Context<UpdateConfig>, params: UpdateConfigParams) -> Result<()> {
+    // sensitive parameters go through queue_change instead
+    ctx.accounts.proposal.consume(
+        &ctx.accounts.multisig,
+        &AdminAction::UpdateConfig {
+            guardian: params.guardian,
+        },
+    )?;
+    let config = &mut ctx.accounts.config;
+    if let Some(guardian) = params.guardian {
+        config.guardian = guardian;
+    }
+    Ok(())
+}
+
+pub fn set_global_pause(ctx: Context<SetGlobalPause>, flags: u8) -> Result<()> {
+    require!(flags & !pause_flags::ALL == 0, ErrorCode::InvalidPauseFlags);
+    let config = &mut ctx.accounts.config;
+    config.pause_flags = flags;
+
+    emit!(PauseFlagsUpdated {
+        target: config.key(),
+        pause_flags: flags,
+        authority: ctx.accounts.guardian.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn set_lottery_pause(ctx: Context<SetLotteryPause>, flags: u8) -> Result<()> {
+    require!(flags & !pause_flags::ALL == 0, ErrorCode::InvalidPauseFlags);
+    let lottery_acct = &mut ctx.accounts.lottery_acct;
+    lottery_acct.pause_flags = flags;
+
+    emit!(PauseFlagsUpdated {
+        target: lottery_acct.key(),
+        pause_flags: flags,
+        authority: ctx.accounts.guardian.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct InitializeConfigParams {
+    pub guardian: Pubkey,
+    pub treasury: Pubkey,
+    pub fee_bps: u16,
+    pub allowed_lending_programs: Vec<Pubkey>,
+    pub allowed_reserves: Vec<Pubkey>,
+    pub allowed_oracle_queues: Vec<Pubkey>,
+    pub timelock_delay: i64,
+    pub allowed_staking_programs: Vec<Pubkey>,
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateConfigParams {
+    pub guardian: Option<Pubkey>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        space = 8 + ProgramConfig::MAX_SIZE,
+        payer = admin,
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    // only the upgrade authority may create the config
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCode::Unauthorized
+    )]
+    program: Program<'info, NoLossLottery>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::Unauthorized
+    )]
+    program_data: Account<'info, ProgramData>,
+    #[account(mut)]
+    admin: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        mut,
+        seeds = [PROPOSAL_STR.as_bytes(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    proposal: Box<Account<'info, AdminProposal>>,
+    #[account(
+        seeds = [MULTISIG_STR.as_bytes(),],
+        bump = multisig.bump,
+    )]
+    multisig: Box<Account<'info, Multisig>>,
+    admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetGlobalPause<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+        constraint = config.is_guardian(guardian.key) @ ErrorCode::Unauthorized,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetLotteryPause<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        constraint = lottery_acct.is_guardian(guardian.key) @ ErrorCode::Unauthorized,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    guardian: Signer<'info>,
+}
@@ -0,0 +1,338 @@
+use crate::errors::ErrorCode;
+use crate::strategies::StrategyKind;
+use crate::{
+    utils::{
+        LOTTERY_INFO_STR, PRIZE_VAULT_STR, REWARDS_VAULT_STR, VAULT_SIGNER_STR,
+        WITHDRAWAL_QUEUE_STR,
+    },
+    EmergencyDeclared, EmergencyShareClaimed, LotteryInfo, LotteryStatus, WithdrawalQueue,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::Token,
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+/// for reserves that stopped honouring redemptions, the lottery stops for good
+/// and participants pull their share of every vault it holds instead
+pub fn declare_emergency(ctx: Context<DeclareEmergency>) -> Result<()> {
+    let lottery_acct = &mut ctx.accounts.lottery_acct;
+    lottery_acct.status = LotteryStatus::Emergency.to_code();
+
+    emit!(EmergencyDeclared {
+        lottery: lottery_acct.key(),
+        guardian: ctx.accounts.guardian.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// pays the participant principal / total_principal of the liquidity, prize and rewards
+/// vaults and of every collateral vault, collateral can be redeemed through the lending
+/// market later. withdrawals still queued claim what they are owed the same way.
+/// remaining accounts are a (collateral mint, collateral vault, user collateral account)
+/// triple for every allocation with a reserve, in slot order
+pub fn claim_emergency_share<'a, 'b, 'c, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, ClaimEmergencyShare<'info>>,
+) -> Result<()> {
+    let lottery_acct = &ctx.accounts.lottery_acct;
+    let user = ctx.accounts.user.key();
+    let (principal, queued) = match lottery_acct.principal_of(user) {
+        Ok(principal) => (principal, false),
+        Err(_) => {
+            let entry = ctx
+                .accounts
+                .withdrawal_queue
+                .as_ref()
+                .and_then(|queue| queue.entries.iter().find(|entry| entry.user == user))
+                .ok_or(ErrorCode::ParticipantNotFound)?;
+            (entry.amount, true)
+        }
+    };
+    let total_principal = lottery_acct.total_principal + lottery_acct.queued_amount;
+    let slots: Vec<_> = (0..=LotteryInfo::EXTRA_ALLOCATIONS as u8)
+        .map(|i| lottery_acct.allocation(i))
+        .filter(|slot| slot.is_set())
+        .collect();
+    require!(
+        ctx.remaining_accounts.len() == slots.len() * 3,
+        ErrorCode::InvalidStrategyAccounts,
+    );
+    // whoever claims last empties the vaults
+    let share = |amount: u64| pro_rata(amount, principal, total_principal);
+    let pda_seeds = &[
+        VAULT_SIGNER_STR.as_bytes(),
+        &[lottery_acct.vault_signer_bump],
+    ];
+
+    let liquidity_amount = share(ctx.accounts.source_liquidity_vault.amount);
+    if liquidity_amount > 0 {
+        token_interface::transfer_checked(
+            ctx.accounts.transfer_liquidity_to_user().with_signer(&[pda_seeds.as_ref()]),
+            liquidity_amount,
+            ctx.accounts.source_liquidity_mint.decimals,
+        )?;
+    }
+
+    // the sponsored prize was never drawn
+    let prize_amount =
+        vault_balance(&ctx.accounts.prize_vault, &lottery_acct.liquidity_mint)?.map_or(0, share);
+    if prize_amount > 0 {
+        token_interface::transfer_checked(
+            ctx.accounts.transfer_prize_to_user().with_signer(&[pda_seeds.as_ref()]),
+            prize_amount,
+            ctx.accounts.source_liquidity_mint.decimals,
+        )?;
+    }
+
+    // neither were the staking rewards, they need the reward accounts once there are any
+    let reward_amount = match (&ctx.accounts.reward_mint, &ctx.accounts.user_reward_account) {
+        (Some(reward_mint), Some(user_reward_account)) => {
+            require!(
+                user_reward_account.mint == reward_mint.key()
+                    && user_reward_account.owner == user,
+                ErrorCode::InvalidStakingAccounts,
+            );
+            let amount = vault_balance(&ctx.accounts.rewards_vault, &reward_mint.key())?
+                .map_or(0, share);
+            if amount > 0 {
+                token_interface::transfer_checked(
+                    ctx.accounts
+                        .transfer_rewards_to_user(reward_mint, user_reward_account)
+                        .with_signer(&[pda_seeds.as_ref()]),
+                    amount,
+                    reward_mint.decimals,
+                )?;
+            }
+            amount
+        }
+        _ => {
+            let rewards = ctx.accounts.rewards_vault.to_account_info();
+            require!(
+                rewards.data_is_empty()
+                    || InterfaceAccount::<TokenAccount>::try_from(&rewards)?.amount == 0,
+                ErrorCode::InvalidStakingAccounts,
+            );
+            0
+        }
+    };
+
+    let mut collateral_amounts = Vec::with_capacity(slots.len());
+    for (slot, accounts) in slots.iter().zip(ctx.remaining_accounts.chunks(3)) {
+        let (mint_info, vault_info, user_info) = (&accounts[0], &accounts[1], &accounts[2]);
+        let mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+        let vault = InterfaceAccount::<TokenAccount>::try_from(vault_info)?;
+        let user_account = InterfaceAccount::<TokenAccount>::try_from(user_info)?;
+        require!(
+            mint.key() == slot.lending.collateral_mint
+                && vault.mint == mint.key()
+                && vault.owner == ctx.accounts.authority.key()
+                && user_account.mint == mint.key()
+                && user_account.owner == user,
+            ErrorCode::InvalidStrategyAccounts,
+        );
+
+        let collateral_amount = share(vault.amount);
+        if collateral_amount > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new(
+                    ctx.accounts.collateral_token_program.to_account_info(),
+                    TransferChecked {
+                        from: vault_info.clone(),
+                        mint: mint_info.clone(),
+                        to: user_info.clone(),
+                        authority: ctx.accounts.authority.to_account_info(),
+                    },
+                )
+                .with_signer(&[pda_seeds.as_ref()]),
+                collateral_amount,
+                mint.decimals,
+            )?;
+        }
+        collateral_amounts.push(collateral_amount);
+    }
+    if queued {
+        if let Some(withdrawal_queue) = &mut ctx.accounts.withdrawal_queue {
+            withdrawal_queue.remove_user(user)?;
+        }
+        ctx.accounts.lottery_acct.queued_amount -= principal;
+    } else {
+        LotteryInfo::remove_participant(&mut ctx.accounts.lottery_acct, user)?;
+    }
+
+    emit!(EmergencyShareClaimed {
+        lottery: ctx.accounts.lottery_acct.key(),
+        participant: user,
+        liquidity_amount,
+        prize_amount,
+        reward_amount,
+        collateral_amounts,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// `principal` / `total_principal` of `amount`, rounded down
+fn pro_rata(amount: u64, principal: u64, total_principal: u64) -> u64 {
+    (amount as u128 * principal as u128 / total_principal as u128) as u64
+}
+
+/// balance of a singleton vault that may never have been created, None if it wasn't
+pub(crate) fn vault_balance(vault: &AccountInfo, mint: &Pubkey) -> Result<Option<u64>> {
+    if vault.data_is_empty() {
+        return Ok(None);
+    }
+    let vault = InterfaceAccount::<TokenAccount>::try_from(vault)?;
+    require!(vault.mint == *mint, ErrorCode::InvalidMint);
+    Ok(Some(vault.amount))
+}
+
+#[derive(Accounts)]
+pub struct DeclareEmergency<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        constraint = lottery_acct.is_guardian(guardian.key) @ ErrorCode::Unauthorized,
+        constraint = lottery_acct.is_rebalanceable() @ ErrorCode::InvalidStatus,
+        constraint = lottery_acct.strategy != StrategyKind::None.to_code() @ ErrorCode::StrategyDisabled,
+        constraint = !lottery_acct.collateral_staked @ ErrorCode::CollateralStaked,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    guardian: Signer<'info>,
+}
+
+// deliberately not gated on the pause flags, this is the way out while everything else is stuck
+#[derive(Accounts)]
+pub struct ClaimEmergencyShare<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        constraint = lottery_acct.status == LotteryStatus::Emergency.to_code() @ ErrorCode::InvalidStatus,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    // only needed by withdrawals that were still queued
+    #[account(
+        mut,
+        seeds = [WITHDRAWAL_QUEUE_STR.as_bytes(),],
+        bump = withdrawal_queue.bump,
+    )]
+    withdrawal_queue: Option<Box<Account<'info, WithdrawalQueue>>>,
+    #[account(mut)]
+    user: Signer<'info>,
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump = lottery_acct.vault_signer_bump,
+        )]
+    authority: AccountInfo<'info>,
+    #[account(
+        constraint = source_liquidity_mint.key() == lottery_acct.liquidity_mint @ ErrorCode::InvalidMint,
+    )]
+    source_liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint=source_liquidity_mint,
+        associated_token::authority=authority,
+        associated_token::token_program=token_program,
+    )]
+    source_liquidity_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint=source_liquidity_mint,
+        token::authority=user,
+    )]
+    user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: may never have been created, checked in the handler
+    #[account(
+        mut,
+        seeds = [PRIZE_VAULT_STR.as_bytes()],
+        bump,
+    )]
+    prize_vault: UncheckedAccount<'info>,
+    /// CHECK: may never have been created, checked in the handler
+    #[account(
+        mut,
+        seeds = [REWARDS_VAULT_STR.as_bytes()],
+        bump,
+    )]
+    rewards_vault: UncheckedAccount<'info>,
+    // required while the rewards vault holds anything
+    reward_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    user_reward_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    token_program: Interface<'info, TokenInterface>,
+    /// lending collateral and Port reward mints are always owned by the legacy token program
+    collateral_token_program: Program<'info, Token>,
+}
+
+impl<'info> ClaimEmergencyShare<'info> {
+    pub fn transfer_liquidity_to_user(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let transfer_acct = TransferChecked {
+            from: self.source_liquidity_vault.to_account_info(),
+            mint: self.source_liquidity_mint.to_account_info(),
+            to: self.user_token_account.to_account_info(),
+            authority: self.authority.to_account_info(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), transfer_acct)
+    }
+
+    pub fn transfer_prize_to_user(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let transfer_acct = TransferChecked {
+            from: self.prize_vault.to_account_info(),
+            mint: self.source_liquidity_mint.to_account_info(),
+            to: self.user_token_account.to_account_info(),
+            authority: self.authority.to_account_info(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), transfer_acct)
+    }
+
+    pub fn transfer_rewards_to_user(
+        &self,
+        reward_mint: &InterfaceAccount<'info, Mint>,
+        user_reward_account: &InterfaceAccount<'info, TokenAccount>,
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let transfer_acct = TransferChecked {
+            from: self.rewards_vault.to_account_info(),
+            mint: reward_mint.to_account_info(),
+            to: user_reward_account.to_account_info(),
+            authority: self.authority.to_account_info(),
+        };
+        CpiContext::new(self.collateral_token_program.to_account_info(), transfer_acct)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn claims_in_any_order_empty_the_vault() {
+        let principals = [300u64, 500, 200];
+        for order in [[0, 1, 2], [2, 1, 0], [1, 2, 0]] {
+            let mut vault = 1_001u64;
+            let mut total_principal: u64 = principals.iter().sum();
+            let mut paid = [0u64; 3];
+            for i in order {
+                let amount = pro_rata(vault, principals[i], total_principal);
+                vault -= amount;
+                total_principal -= principals[i];
+                paid[i] = amount;
+            }
+            assert_eq!(vault, 0);
+            // every share is within a token of principal / total of the vault
+            for (principal, paid) in principals.iter().zip(paid) {
+                let exact = 1_001 * principal / 1_000;
+                assert!(paid.abs_diff(exact) <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn queued_withdrawals_share_with_participants() {
+        // 600 still in the draw, 400 queued
+        assert_eq!(pro_rata(500, 400, 600 + 400), 200);
+        assert_eq!(pro_rata(500, 600, 600 + 400), 300);
+    }
+}
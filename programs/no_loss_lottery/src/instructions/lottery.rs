@@ -1,20 +1,28 @@
 use std::mem;
 
-use crate::{VrfClient, STATE_SEED, LotteryStatus};
+use crate::{VrfClient, LotteryStatus};
 use crate::errors::ErrorCode;
+use crate::instructions::emergency::vault_balance;
+use crate::instructions::strategy::*;
+use crate::strategies::StrategyKind;
 use crate::{
-    utils::{LOTTERY_INFO_STR, VAULT_SIGNER_STR},
-    LotteryInfo, MAX_RESULT,
+    pause_flags,
+    utils::{
+        CONFIG_STR, LOTTERY_INFO_STR, MAX_RESULT, MULTISIG_STR, PRIZE_VAULT_STR, PROPOSAL_STR,
+        REWARDS_VAULT_STR, STAKING_INFO_STR, STATE_SEED, UNWRAP_STR, VAULT_SIGNER_STR,
+    },
+    AdminAction, AdminProposal, FundsInvested, FundsRedeemed, LendingReserve, LotteryCancelled,
+    LotteryInfo, LotteryLocked, Multisig, ParticipantLeft, PrizeFunded, ProgramConfig,
+    RoundImpaired, StakingInfo, YieldHarvested,
 };
-use anchor_lang::{prelude::*, solana_program};
-use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::{prelude::*, solana_program, system_program};
 
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount, Transfer},
-};
-use port_variable_rate_lending_instructions::instruction::{
-    deposit_reserve_liquidity, redeem_reserve_collateral, refresh_reserve,
+    associated_token::{get_associated_token_address, AssociatedToken},
+    token::{spl_token::native_mint, Token},
+    token_interface::{
+        self, CloseAccount, Mint, SyncNative, TokenAccount, TokenInterface, TransferChecked,
+    },
 };
 use switchboard_v2::{SWITCHBOARD_PROGRAM_ID, VrfAccountData};
 
@@ -28,148 +36,492 @@ pub fn initialize_lottery(
         &mut ctx.accounts.lottery_acct,
         params.entry_fee,
         params.max_participants,
+        ctx.accounts.user.key(),
+        params.operator,
+        params.guardian,
+        ctx.accounts.state.key(),
+        ctx.accounts.user.key(),
+        StrategyKind::from(params.strategy)?,
+        ctx.accounts.source_liquidity_mint.key(),
     )?;
+    if let Some(lending) = ctx.accounts.lending_reserve(&params)? {
+        ctx.accounts.lottery_acct.lending = lending;
+    }
+    ctx.accounts.lottery_acct.vault_signer_bump = *ctx.bumps.get("vault_signer").unwrap();
+    ctx.accounts.lottery_acct.invest_on_entry = params.invest_on_entry;
+    ctx.accounts.lottery_acct.set_buffer_bps(params.buffer_bps)?;
     Ok(())
 }
 
 pub fn enter_lottery(ctx: Context<EnterLottery>) -> Result<()> {
+    let vault_amount_before = ctx.accounts.source_liquidity_vault.amount;
     // transfer tokens from user to source_liquidity_vault
-    anchor_spl::token::transfer(
+    token_interface::transfer_checked(
         ctx.accounts.transfer_tokens_to_source_liquidity_vault(),
         ctx.accounts.lottery_acct.entry_fee,
-    )
-    .expect("transfer failed");
-    LotteryInfo::add_participant(&mut ctx.accounts.lottery_acct, ctx.accounts.user.key())?;
+        ctx.accounts.source_liquidity_mint.decimals,
+    )?;
+    // credit what actually landed in the vault, transfer-fee mints deliver less than entry_fee
+    ctx.accounts.source_liquidity_vault.reload()?;
+    let principal = ctx.accounts.source_liquidity_vault.amount - vault_amount_before;
+    LotteryInfo::add_participant(
+        &mut ctx.accounts.lottery_acct,
+        ctx.accounts.user.key(),
+        principal,
+        Clock::get()?.unix_timestamp,
+    )?;
+    Ok(())
+}
+
+pub fn enter_lottery_native(ctx: Context<EnterLotteryNative>) -> Result<()> {
+    // wrap lamports straight into the wSOL source_liquidity_vault
+    system_program::transfer(
+        ctx.accounts.transfer_lamports_to_source_liquidity_vault(),
+        ctx.accounts.lottery_acct.entry_fee,
+    )?;
+    token_interface::sync_native(ctx.accounts.sync_source_liquidity_vault())?;
+    let entry_fee = ctx.accounts.lottery_acct.entry_fee;
+    LotteryInfo::add_participant(
+        &mut ctx.accounts.lottery_acct,
+        ctx.accounts.user.key(),
+        entry_fee,
+        Clock::get()?.unix_timestamp,
+    )?;
+    Ok(())
+}
+
+/// enters the lottery and moves the entry straight into the reserve
+pub fn enter_lottery_invested<'a, 'b, 'c, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, EnterLotteryInvested<'info>>,
+) -> Result<()> {
+    let vault_amount_before = ctx.accounts.source_liquidity_vault.amount;
+    token_interface::transfer_checked(
+        ctx.accounts.transfer_tokens_to_source_liquidity_vault(),
+        ctx.accounts.lottery_acct.entry_fee,
+        ctx.accounts.source_liquidity_mint.decimals,
+    )?;
+    ctx.accounts.source_liquidity_vault.reload()?;
+    let principal = ctx.accounts.source_liquidity_vault.amount - vault_amount_before;
+
+    // part of the entry tops up the liquidity buffer instead of being invested
+    let lottery_acct = &ctx.accounts.lottery_acct;
+    let buffer_target = lottery_acct.buffer_target(lottery_acct.total_principal + principal);
+    let liquidity_amount =
+        principal - buffer_target.saturating_sub(vault_amount_before).min(principal);
+
+    if liquidity_amount > 0 {
+        let strategy = StrategyKind::from(ctx.accounts.lottery_acct.strategy)?;
+        let accounts = ctx.accounts.lending.strategy_accounts(
+            &ctx.accounts.source_liquidity_mint,
+            &ctx.accounts.source_liquidity_vault,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+        strategy.validate(&accounts)?;
+        strategy.refresh(&accounts)?;
+
+        let collateral_before = ctx.accounts.lending.collateral_amount()?;
+        let pda_seeds = &[
+            VAULT_SIGNER_STR.as_bytes(),
+            &[ctx.accounts.lottery_acct.vault_signer_bump],
+        ];
+        strategy.deposit(&accounts, liquidity_amount, &[pda_seeds.as_ref()])?;
+
+        let collateral_amount =
+            ctx.accounts.lending.collateral_amount()? - collateral_before;
+        let lottery_acct = &mut ctx.accounts.lottery_acct;
+        lottery_acct.invested_amount += liquidity_amount;
+        lottery_acct.collateral_received += collateral_amount;
+        if lottery_acct.invested_at == 0 {
+            lottery_acct.invested_at = Clock::get()?.unix_timestamp;
+        }
+
+        emit!(FundsInvested {
+            lottery: lottery_acct.key(),
+            liquidity_amount,
+            collateral_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    }
+    LotteryInfo::add_participant(
+        &mut ctx.accounts.lottery_acct,
+        ctx.accounts.user.key(),
+        principal,
+        Clock::get()?.unix_timestamp,
+    )?;
     Ok(())
 }
+
+/// leaves the lottery before it locks, redeeming collateral first when the
+/// lottery invests on entry and the liquidity buffer can't cover the principal
+pub fn leave_lottery<'a, 'b, 'c, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, LeaveLottery<'info>>,
+) -> Result<()> {
+    let principal = ctx.accounts.lottery_acct.principal_of(ctx.accounts.user.key())?;
+    let pda_seeds = &[
+        VAULT_SIGNER_STR.as_bytes(),
+        &[ctx.accounts.lottery_acct.vault_signer_bump],
+    ];
+
+    let shortfall = principal.saturating_sub(ctx.accounts.source_liquidity_vault.amount);
+    if ctx.accounts.lottery_acct.invest_on_entry && shortfall > 0 {
+        require!(
+            ctx.accounts.config.is_active(&ctx.accounts.lottery_acct, pause_flags::LENDING_WITHDRAW),
+            ErrorCode::Paused,
+        );
+        require!(!ctx.accounts.lottery_acct.collateral_staked, ErrorCode::CollateralStaked);
+        let strategy = StrategyKind::from(ctx.accounts.lottery_acct.strategy)?;
+        let accounts = ctx.accounts.lending.strategy_accounts(
+            &ctx.accounts.source_liquidity_mint,
+            &ctx.accounts.source_liquidity_vault,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+        strategy.validate(&accounts)?;
+        strategy.refresh(&accounts)?;
+
+        let collateral_amount = ctx.accounts.lottery_acct.collateral_for(shortfall)?;
+        let liquidity_before = ctx.accounts.source_liquidity_vault.amount;
+        strategy.redeem(&accounts, collateral_amount, &[pda_seeds.as_ref()])?;
+        ctx.accounts.source_liquidity_vault.reload()?;
+        let liquidity_amount = ctx.accounts.source_liquidity_vault.amount - liquidity_before;
+
+        let lottery_acct = &mut ctx.accounts.lottery_acct;
+        lottery_acct.invested_amount -= shortfall.min(lottery_acct.invested_amount);
+        lottery_acct.collateral_received -= collateral_amount;
+        emit!(FundsRedeemed {
+            lottery: lottery_acct.key(),
+            collateral_amount,
+            liquidity_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    }
+
+    // a reserve that lost value can hand back less than the principal
+    let amount = principal.min(ctx.accounts.source_liquidity_vault.amount);
+    token_interface::transfer_checked(
+        ctx.accounts.transfer_tokens_to_user().with_signer(&[pda_seeds.as_ref()]),
+        amount,
+        ctx.accounts.source_liquidity_mint.decimals,
+    )?;
+    LotteryInfo::remove_participant(&mut ctx.accounts.lottery_acct, ctx.accounts.user.key())?;
+
+    emit!(ParticipantLeft {
+        lottery: ctx.accounts.lottery_acct.key(),
+        participant: ctx.accounts.user.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn lock_lottery(ctx: Context<LockLottery>) -> Result<()> {
+    let lottery_acct = &mut ctx.accounts.lottery_acct;
+    lottery_acct.lock(Clock::get()?.unix_timestamp);
+
+    emit!(LotteryLocked {
+        lottery: lottery_acct.key(),
+        participants: lottery_acct.participants.len() as u64,
+        total_principal: lottery_acct.total_principal,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// invests the allocation's share of the liquidity the buffer doesn't keep,
+/// the first deposit into any allocation moves the lottery to Invested
 pub fn deposit<'a, 'b, 'c, 'info>(
     ctx: Context<'a, 'b, 'c, 'info, DepositTokensToLendingPool<'info>>,
-    vault_signer_bump: u8,
+    allocation: u8,
 ) -> Result<()> {
-    let refresh_ix = refresh_reserve(
-        ctx.accounts.lending_program.key(),
-        ctx.accounts.reserve.key(),
-        anchor_lang::solana_program::program_option::COption::Some(
-            ctx.accounts.reserve_liquidity_oracle.key(),
-        ),
-    );
-    let mut accounts = vec![
-        ctx.accounts.lending_program.to_account_info(),
-        ctx.accounts.reserve.to_account_info(),
-        ctx.accounts.reserve_liquidity_oracle.to_account_info(),
-        ctx.accounts.clock.to_account_info(),
+    let mut slot = ctx.accounts.lottery_acct.allocation(allocation);
+    let lottery_acct = &ctx.accounts.lottery_acct;
+    let liquidity_amount = ctx.accounts.source_liquidity_vault.amount
+        .saturating_sub(lottery_acct.buffer_target(lottery_acct.total_principal))
+        .min(lottery_acct.allocation_target(allocation).saturating_sub(slot.invested_amount));
+
+    // the buffer may keep the whole vault, the lottery still moves to Invested
+    // but there is nothing to hand to the reserve
+    let collateral_amount = if liquidity_amount > 0 {
+        let strategy = StrategyKind::from(slot.strategy)?;
+        let accounts = ctx.accounts.lending.strategy_accounts(
+            &ctx.accounts.source_liquidity_mint,
+            &ctx.accounts.source_liquidity_vault,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+        strategy.validate(&accounts)?;
+        strategy.refresh(&accounts)?;
+
+        let collateral_before = ctx.accounts.lending.collateral_amount()?;
+        let pda_seeds = &[
+            VAULT_SIGNER_STR.as_bytes(),
+            &[ctx.accounts.lottery_acct.vault_signer_bump],
+        ];
+        strategy.deposit(&accounts, liquidity_amount, &[pda_seeds.as_ref()])?;
+        ctx.accounts.lending.collateral_amount()? - collateral_before
+    } else {
+        0
+    };
+    slot.invested_amount += liquidity_amount;
+    slot.collateral_received += collateral_amount;
+    let lottery_acct = &mut ctx.accounts.lottery_acct;
+    lottery_acct.set_allocation(allocation, slot);
+    lottery_acct.status = LotteryStatus::Invested.to_code();
+    if lottery_acct.invested_at == 0 {
+        lottery_acct.invested_at = Clock::get()?.unix_timestamp;
+    }
+
+    emit!(FundsInvested {
+        lottery: lottery_acct.key(),
+        liquidity_amount,
+        collateral_amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// moves the allocation back to its target weight, redeeming what it holds over
+/// the target and investing from the liquidity vault (above its buffer) when under.
+/// rebalancing the overweight allocations first frees liquidity for the others
+pub fn rebalance<'a, 'b, 'c, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, RebalanceLiquidityBuffer<'info>>,
+    allocation: u8,
+) -> Result<()> {
+    let mut slot = ctx.accounts.lottery_acct.allocation(allocation);
+    let lottery_acct = &ctx.accounts.lottery_acct;
+    let target = lottery_acct.allocation_target(allocation);
+    let buffer_target = lottery_acct.buffer_target(lottery_acct.total_principal);
+    let liquidity = ctx.accounts.source_liquidity_vault.amount;
+    if slot.invested_amount == target {
+        return Ok(());
+    }
+
+    let strategy = StrategyKind::from(slot.strategy)?;
+    let accounts = ctx.accounts.lending.strategy_accounts(
+        &ctx.accounts.source_liquidity_mint,
+        &ctx.accounts.source_liquidity_vault,
+        &ctx.accounts.authority,
+        ctx.remaining_accounts,
+    )?;
+    strategy.validate(&accounts)?;
+    strategy.refresh(&accounts)?;
+    let pda_seeds = &[
+        VAULT_SIGNER_STR.as_bytes(),
+        &[ctx.accounts.lottery_acct.vault_signer_bump],
     ];
-    accounts.extend_from_slice(&ctx.remaining_accounts[..]);
 
-    invoke(&refresh_ix, &accounts)?;
+    if slot.invested_amount < target {
+        require!(
+            ctx.accounts.config.is_active(&ctx.accounts.lottery_acct, pause_flags::LENDING_DEPOSIT),
+            ErrorCode::Paused,
+        );
+        let liquidity_amount = (target - slot.invested_amount)
+            .min(liquidity.saturating_sub(buffer_target));
+        if liquidity_amount == 0 {
+            return Ok(());
+        }
+        let collateral_before = ctx.accounts.lending.collateral_amount()?;
+        strategy.deposit(&accounts, liquidity_amount, &[pda_seeds.as_ref()])?;
 
-    let deposit_ix = deposit_reserve_liquidity(
-        ctx.accounts.lending_program.key(),
-        ctx.accounts.source_liquidity_vault.amount,
-        ctx.accounts.source_liquidity_vault.key(),
-        ctx.accounts.destination_collateral_vault.key(),
-        ctx.accounts.reserve.key(),
-        ctx.accounts.reserve_liquidity_supply.key(),
-        ctx.accounts.reserve_collateral_mint.key(),
-        ctx.accounts.lending_market.key(),
-        ctx.accounts.authority.key(),
-    );
-    let mut accounts = vec![
-        ctx.accounts.lending_program.to_account_info(),
-        ctx.accounts.source_liquidity_vault.to_account_info(),
-        ctx.accounts.destination_collateral_vault.to_account_info(),
-        ctx.accounts.reserve.to_account_info(),
-        ctx.accounts.reserve_liquidity_supply.to_account_info(),
-        ctx.accounts.reserve_collateral_mint.to_account_info(),
-        ctx.accounts.lending_market.to_account_info(),
-        ctx.accounts.authority.to_account_info(),
-        ctx.accounts.clock.to_account_info(),
+        let collateral_amount =
+            ctx.accounts.lending.collateral_amount()? - collateral_before;
+        slot.invested_amount += liquidity_amount;
+        slot.collateral_received += collateral_amount;
+        let lottery_acct = &mut ctx.accounts.lottery_acct;
+        lottery_acct.set_allocation(allocation, slot);
+
+        emit!(FundsInvested {
+            lottery: lottery_acct.key(),
+            liquidity_amount,
+            collateral_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    } else {
+        require!(
+            ctx.accounts.config.is_active(&ctx.accounts.lottery_acct, pause_flags::LENDING_WITHDRAW),
+            ErrorCode::Paused,
+        );
+        let excess = slot.invested_amount - target;
+        let collateral_amount = slot.collateral_for(excess)?;
+        strategy.redeem(&accounts, collateral_amount, &[pda_seeds.as_ref()])?;
+
+        ctx.accounts.source_liquidity_vault.reload()?;
+        let liquidity_amount = ctx.accounts.source_liquidity_vault.amount - liquidity;
+        slot.invested_amount -= excess;
+        slot.collateral_received -= collateral_amount;
+        let lottery_acct = &mut ctx.accounts.lottery_acct;
+        lottery_acct.set_allocation(allocation, slot);
+
+        emit!(FundsRedeemed {
+            lottery: lottery_acct.key(),
+            collateral_amount,
+            liquidity_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    }
+    Ok(())
+}
+
+/// redeems only the collateral backing yield above the principal invested in
+/// the allocation and moves the proceeds into the prize vault, principal stays invested
+pub fn harvest<'a, 'b, 'c, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, HarvestYield<'info>>,
+    allocation: u8,
+) -> Result<()> {
+    let mut slot = ctx.accounts.lottery_acct.allocation(allocation);
+    let strategy = StrategyKind::from(slot.strategy)?;
+    let accounts = ctx.accounts.lending.strategy_accounts(
+        &ctx.accounts.source_liquidity_mint,
+        &ctx.accounts.source_liquidity_vault,
+        &ctx.accounts.authority,
+        ctx.remaining_accounts,
+    )?;
+    strategy.validate(&accounts)?;
+    strategy.refresh(&accounts)?;
+
+    // exchange rate as of the refresh above
+    let reserve_info = strategy.reserve_info(accounts.lending_program.key, &accounts.reserve)?;
+    let collateral_value =
+        reserve_info.collateral_value(ctx.accounts.lending.collateral_amount()?);
+    let yield_amount = collateral_value.saturating_sub(slot.invested_amount);
+    let collateral_amount = reserve_info.collateral_for_liquidity(yield_amount);
+    require!(collateral_amount > 0, ErrorCode::NothingToHarvest);
+
+    let liquidity_before = ctx.accounts.source_liquidity_vault.amount;
+    let pda_seeds = &[
+        VAULT_SIGNER_STR.as_bytes(),
+        &[ctx.accounts.lottery_acct.vault_signer_bump],
     ];
-    // let mut accounts = ctx.accounts.to_account_infos();
-    accounts.extend_from_slice(&ctx.remaining_accounts[..]);
-    let pda_seeds = &[VAULT_SIGNER_STR.as_bytes(), &[vault_signer_bump]];
-    // invoke(&deposit_ix, &account)?;
-    invoke_signed(&deposit_ix, &accounts, &[pda_seeds.as_ref()])?;
+    strategy.redeem(&accounts, collateral_amount, &[pda_seeds.as_ref()])?;
+    ctx.accounts.source_liquidity_vault.reload()?;
+    let harvested = ctx.accounts.source_liquidity_vault.amount - liquidity_before;
 
+    token_interface::transfer_checked(
+        ctx.accounts.transfer_yield_to_prize_vault().with_signer(&[pda_seeds.as_ref()]),
+        harvested,
+        ctx.accounts.source_liquidity_mint.decimals,
+    )?;
+    slot.collateral_received = slot.collateral_received.saturating_sub(collateral_amount);
+    let lottery_acct = &mut ctx.accounts.lottery_acct;
+    lottery_acct.set_allocation(allocation, slot);
+
+    emit!(YieldHarvested {
+        lottery: lottery_acct.key(),
+        collateral_amount,
+        yield_amount: harvested,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
     Ok(())
 }
 
+/// redeems everything the allocation holds, the lottery is Redeemed once
+/// no allocation holds collateral anymore. a cancelled lottery stays Cancelled
+/// and refunds once everything is back
 pub fn withdraw<'a, 'b, 'c, 'info>(
     ctx: Context<'a, 'b, 'c, 'info, WithdrawTokensFromLendingPool<'info>>,
-    vault_signer_bump: u8,
+    allocation: u8,
 ) -> Result<()> {
+    let mut slot = ctx.accounts.lottery_acct.allocation(allocation);
+    let strategy = StrategyKind::from(slot.strategy)?;
+    let accounts = ctx.accounts.lending.strategy_accounts(
+        &ctx.accounts.source_liquidity_mint,
+        &ctx.accounts.source_liquidity_vault,
+        &ctx.accounts.authority,
+        ctx.remaining_accounts,
+    )?;
+    strategy.validate(&accounts)?;
+    strategy.refresh(&accounts)?;
 
-    let refresh_ix = refresh_reserve(
-        ctx.accounts.lending_program.key(),
-        ctx.accounts.reserve.key(),
-        anchor_lang::solana_program::program_option::COption::Some(
-            ctx.accounts.reserve_liquidity_oracle.key(),
-        ),
-    );
-    let mut accounts = vec![
-        ctx.accounts.lending_program.to_account_info(),
-        ctx.accounts.reserve.to_account_info(),
-        ctx.accounts.reserve_liquidity_oracle.to_account_info(),
-        ctx.accounts.clock.to_account_info(),
-    ];
-    accounts.extend_from_slice(&ctx.remaining_accounts[..]);
-
-    invoke(&refresh_ix, &accounts)?;
-
-    let withdraw_ix = redeem_reserve_collateral(
-        ctx.accounts.lending_program.key(),
-        ctx.accounts.destination_collateral_vault.amount,
-        ctx.accounts.destination_collateral_vault.key(),
-        ctx.accounts.source_liquidity_vault.key(),
-        ctx.accounts.reserve.key(),
-        ctx.accounts.reserve_collateral_mint.key(),
-        ctx.accounts.reserve_liquidity_supply.key(),
-        ctx.accounts.lending_market.key(),
-        ctx.accounts.authority.key(),
-    );
-    let mut accounts = vec![
-        ctx.accounts.lending_program.to_account_info(),
-        ctx.accounts.destination_collateral_vault.to_account_info(),
-        ctx.accounts.source_liquidity_vault.to_account_info(),
-        ctx.accounts.reserve.to_account_info(),
-        ctx.accounts.reserve_collateral_mint.to_account_info(),
-        ctx.accounts.reserve_liquidity_supply.to_account_info(),
-        ctx.accounts.lending_market.to_account_info(),
-        ctx.accounts.authority.to_account_info(),
-        ctx.accounts.clock.to_account_info(),
+    let collateral_amount = ctx.accounts.lending.collateral_amount()?;
+    let liquidity_before = ctx.accounts.source_liquidity_vault.amount;
+    let pda_seeds = &[
+        VAULT_SIGNER_STR.as_bytes(),
+        &[ctx.accounts.lottery_acct.vault_signer_bump],
     ];
-    // let mut accounts = ctx.accounts.to_account_infos();
-    accounts.extend_from_slice(&ctx.remaining_accounts[..]);
-    let pda_seeds = &[VAULT_SIGNER_STR.as_bytes(), &[vault_signer_bump]];
-    // invoke(&withdraw_ix, &account)?;
-    invoke_signed(&withdraw_ix, &accounts, &[pda_seeds.as_ref()])?;
+    strategy.redeem(&accounts, collateral_amount, &[pda_seeds.as_ref()])?;
 
+    ctx.accounts.source_liquidity_vault.reload()?;
+    let liquidity_amount = ctx.accounts.source_liquidity_vault.amount - liquidity_before;
+    slot.collateral_received = 0;
+    let lottery_acct = &mut ctx.accounts.lottery_acct;
+    lottery_acct.set_allocation(allocation, slot);
+    lottery_acct.redeemed_amount += liquidity_amount;
+    if lottery_acct.status == LotteryStatus::Invested.to_code()
+        && lottery_acct.is_fully_redeemed()
+    {
+        lottery_acct.status = LotteryStatus::Redeemed.to_code();
+    }
+
+    emit!(FundsRedeemed {
+        lottery: lottery_acct.key(),
+        collateral_amount,
+        liquidity_amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
     Ok(())
 }
 
 pub fn withdraw_user_tokens(
     ctx: Context<WithdrawUserTokens>
 ) -> Result<()> {
-    require!(
-        LotteryStatus::from(ctx.accounts.lottery_acct.status).unwrap() == LotteryStatus::Completed, 
-        ErrorCode::LotteryStillOn,
-    );
-    let  winner = ctx.accounts.lottery_acct.winner.unwrap();
-    let mut amount_to_pay = ctx.accounts.lottery_acct.entry_fee;
-    if ctx.accounts.user.key() == winner {
-        let amount_to_pay_non_winners = amount_to_pay * (ctx.accounts.lottery_acct.max_participants - 1);
-         amount_to_pay = ctx.accounts.source_liquidity_vault.amount - amount_to_pay_non_winners; //includes extra made from lending investment
-
-    }
+    let amount_owed = ctx.accounts.lottery_acct.amount_owed(
+        ctx.accounts.user.key(),
+        ctx.accounts.source_liquidity_vault.amount,
+    )?;
+    let principal = ctx.accounts.lottery_acct.principal_of(ctx.accounts.user.key())?;
+    let fee = ctx.accounts.config.fee_on(amount_owed.saturating_sub(principal));
+    let amount_to_pay = amount_owed - fee;
     // transfer tokens back to user
     let bump = *ctx.bumps.get("vault_signer").unwrap();
     let pda_seeds = &[VAULT_SIGNER_STR.as_bytes(), &[bump]];
 
-    anchor_spl::token::transfer(
+    if fee > 0 {
+        token_interface::transfer_checked(
+            ctx.accounts.transfer_fee_to_treasury().with_signer(&[pda_seeds.as_ref()]),
+            fee,
+            ctx.accounts.source_liquidity_mint.decimals,
+        )?;
+    }
+    token_interface::transfer_checked(
         ctx.accounts.transfer_tokens_from_source_liquidity_vault().with_signer(&[pda_seeds.as_ref()]),
         amount_to_pay,
-    )
-    .expect("transfer failed");
+        ctx.accounts.source_liquidity_mint.decimals,
+    )?;
+    LotteryInfo::remove_participant(&mut ctx.accounts.lottery_acct, ctx.accounts.user.key())?;
+    Ok(())
+}
+
+pub fn withdraw_user_tokens_native(
+    ctx: Context<WithdrawUserTokensNative>
+) -> Result<()> {
+    let amount_owed = ctx.accounts.lottery_acct.amount_owed(
+        ctx.accounts.user.key(),
+        ctx.accounts.source_liquidity_vault.amount,
+    )?;
+    let principal = ctx.accounts.lottery_acct.principal_of(ctx.accounts.user.key())?;
+    let fee = ctx.accounts.config.fee_on(amount_owed.saturating_sub(principal));
+    let amount_to_pay = amount_owed - fee;
+    let bump = *ctx.bumps.get("vault_signer").unwrap();
+    let pda_seeds = &[VAULT_SIGNER_STR.as_bytes(), &[bump]];
+
+    if fee > 0 {
+        token_interface::transfer_checked(
+            ctx.accounts.transfer_fee_to_treasury().with_signer(&[pda_seeds.as_ref()]),
+            fee,
+            ctx.accounts.source_liquidity_mint.decimals,
+        )?;
+    }
+
+    // move the wSOL into a throwaway account and close it so the user receives lamports
+    token_interface::transfer_checked(
+        ctx.accounts.transfer_tokens_to_unwrap_account().with_signer(&[pda_seeds.as_ref()]),
+        amount_to_pay,
+        ctx.accounts.source_liquidity_mint.decimals,
+    )?;
+    token_interface::close_account(
+        ctx.accounts.close_unwrap_account().with_signer(&[pda_seeds.as_ref()]),
+    )?;
     LotteryInfo::remove_participant(&mut ctx.accounts.lottery_acct, ctx.accounts.user.key())?;
     Ok(())
 }
@@ -177,21 +529,193 @@ pub fn withdraw_user_tokens(
 pub fn choose_winner(
     ctx: Context<ChooseWinner>,
 ) -> Result<()>{
+    let status = LotteryStatus::from(ctx.accounts.lottery_acct.status)?;
     require!(
-        LotteryStatus::from(ctx.accounts.lottery_acct.status).unwrap() == LotteryStatus::Started, 
+        status != LotteryStatus::Completed && status != LotteryStatus::Settled,
         ErrorCode::WinnerAlreadySelected,
     );
+    // the draw happens once the funds are back in the liquidity vault
+    let ready = if StrategyKind::from(ctx.accounts.lottery_acct.strategy)? == StrategyKind::None {
+        LotteryStatus::Locked
+    } else {
+        LotteryStatus::Redeemed
+    };
+    require!(status == ready, ErrorCode::InvalidStatus);
     let state = ctx.accounts.state.load()?;
-   
-    let lottery_winner = ctx.accounts.lottery_acct.participants[state.result as usize];
-    ctx.accounts.lottery_acct.winner = Some(lottery_winner);
-    ctx.accounts.lottery_acct.status = LotteryStatus::Completed.to_code();
+    state.check_drawn_after(ctx.accounts.lottery_acct.locked_at)?;
+    let index = state.draw(ctx.accounts.lottery_acct.participants.len())?;
+    let lottery_winner = ctx.accounts.lottery_acct.participants[index].pubkey;
+    drop(state);
+
+    // sponsored prizes and harvested yield join the principal so the winner's claim
+    // picks them up as surplus, nothing else empties the vault before close_accounts
+    let prize = vault_balance(&ctx.accounts.prize_vault, &ctx.accounts.lottery_acct.liquidity_mint)?
+        .unwrap_or(0);
+    if prize > 0 {
+        let bump = *ctx.bumps.get("vault_signer").unwrap();
+        let pda_seeds = &[VAULT_SIGNER_STR.as_bytes(), &[bump]];
+        token_interface::transfer_checked(
+            ctx.accounts
+                .transfer_prize_to_source_liquidity_vault()
+                .with_signer(&[pda_seeds.as_ref()]),
+            prize,
+            ctx.accounts.source_liquidity_mint.decimals,
+        )?;
+        ctx.accounts.source_liquidity_vault.reload()?;
+    }
+
+    let available = ctx.accounts.source_liquidity_vault.amount;
+    let lottery_acct = &mut ctx.accounts.lottery_acct;
+    lottery_acct.status = LotteryStatus::Completed.to_code();
+    if available < lottery_acct.total_principal {
+        // the reserve lost money, no prize and everyone shares the loss
+        lottery_acct.impaired = true;
+        lottery_acct.settlement_liquidity = available;
+        lottery_acct.settlement_principal = lottery_acct.total_principal;
+        emit!(RoundImpaired {
+            lottery: lottery_acct.key(),
+            total_principal: lottery_acct.total_principal,
+            available,
+            shortfall: lottery_acct.total_principal - available,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    } else {
+        lottery_acct.winner = Some(lottery_winner);
+    }
     Ok(())
 }
-pub fn close_accounts(
-    _ctx: Context<CloseAccounts>
+
+pub fn fund_prize(ctx: Context<FundPrize>, amount: u64) -> Result<()> {
+    token_interface::transfer_checked(
+        ctx.accounts.transfer_tokens_to_prize_vault(),
+        amount,
+        ctx.accounts.source_liquidity_mint.decimals,
+    )?;
+
+    emit!(PrizeFunded {
+        lottery: ctx.accounts.lottery_acct.key(),
+        sponsor: ctx.accounts.sponsor.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+pub fn cancel_lottery(
+    ctx: Context<CancelLottery>
+) -> Result<()> {
+    ctx.accounts
+        .proposal
+        .consume(&ctx.accounts.multisig, &AdminAction::CancelLottery)?;
+    ctx.accounts.lottery_acct.status = LotteryStatus::Cancelled.to_code();
+
+    emit!(LotteryCancelled {
+        lottery: ctx.accounts.lottery_acct.key(),
+        authority: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// closes the lottery once nobody is owed anything, every vault it ever funded has to
+/// be empty. remaining accounts are the collateral vaults of the extra allocations
+/// with a reserve, in slot order, followed by every registered VRF client other than
+/// the selected one, in registration order
+pub fn close_accounts<'a, 'b, 'c, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, CloseAccounts<'info>>,
 ) -> Result<()> {
+    ctx.accounts
+        .proposal
+        .consume(&ctx.accounts.multisig, &AdminAction::CloseAccounts)?;
+    if let Some(destination_collateral_vault) = &ctx.accounts.destination_collateral_vault {
+        require!(
+            destination_collateral_vault.amount == 0,
+            ErrorCode::VaultNotEmpty,
+        );
+    } else {
+        require!(
+            StrategyKind::from(ctx.accounts.lottery_acct.strategy)? == StrategyKind::None,
+            ErrorCode::LendingAccountsRequired,
+        );
+    }
+
+    let bump = *ctx.bumps.get("vault_signer").unwrap();
+    let pda_seeds = &[VAULT_SIGNER_STR.as_bytes(), &[bump]];
+
+    // the singleton vaults would otherwise carry their balance into the next lottery
+    let mut remaining_accounts = ctx.remaining_accounts.iter();
+    for index in 1..=LotteryInfo::EXTRA_ALLOCATIONS as u8 {
+        let slot = ctx.accounts.lottery_acct.allocation(index);
+        if !slot.is_set() {
+            continue;
+        }
+        let vault = remaining_accounts
+            .next()
+            .ok_or_else(|| error!(ErrorCode::LendingAccountsRequired))?;
+        require!(
+            *vault.key
+                == get_associated_token_address(
+                    &ctx.accounts.vault_signer.key(),
+                    &slot.lending.collateral_mint,
+                ),
+            ErrorCode::InvalidStrategyAccounts,
+        );
+        ctx.accounts.close_empty_vault(vault, &[pda_seeds.as_ref()])?;
+    }
+    ctx.accounts
+        .close_empty_vault(&ctx.accounts.prize_vault.to_account_info(), &[pda_seeds.as_ref()])?;
+    ctx.accounts
+        .close_empty_vault(&ctx.accounts.rewards_vault.to_account_info(), &[pda_seeds.as_ref()])?;
+    // the selected client is closed through `state`, the standby ones would keep
+    // their rent and block registering clients under the same seeds again
+    for vrf_client in ctx.accounts.lottery_acct.vrf_clients {
+        if vrf_client == Pubkey::default() || vrf_client == ctx.accounts.lottery_acct.vrf_client {
+            continue;
+        }
+        let state = remaining_accounts
+            .next()
+            .ok_or_else(|| error!(ErrorCode::InvalidVrfAccount))?;
+        require!(*state.key == vrf_client, ErrorCode::InvalidVrfAccount);
+        AccountLoader::<VrfClient>::try_from(state)?.close(ctx.accounts.payer.to_account_info())?;
+    }
 
+    // the staking position is a singleton too, the next lottery stakes through a new one
+    if !ctx.accounts.staking_info.data_is_empty() {
+        let staking_info =
+            Account::<StakingInfo>::try_from(&ctx.accounts.staking_info.to_account_info())?;
+        require!(staking_info.staked_amount == 0, ErrorCode::CollateralStaked);
+        staking_info.close(ctx.accounts.payer.to_account_info())?;
+    }
+
+    // nobody is owed anything anymore, whatever is left is dust
+    let dust = ctx.accounts.source_liquidity_vault.amount;
+    if dust > 0 {
+        token_interface::transfer_checked(
+            ctx.accounts.sweep_dust_to_treasury().with_signer(&[pda_seeds.as_ref()]),
+            dust,
+            ctx.accounts.source_liquidity_mint.decimals,
+        )?;
+    }
+    token_interface::close_account(
+        ctx.accounts
+            .close_vault(
+                ctx.accounts.source_liquidity_vault.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            )
+            .with_signer(&[pda_seeds.as_ref()]),
+    )?;
+    if let (Some(destination_collateral_vault), Some(collateral_token_program)) = (
+        &ctx.accounts.destination_collateral_vault,
+        &ctx.accounts.collateral_token_program,
+    ) {
+        token_interface::close_account(
+            ctx.accounts
+                .close_vault(
+                    destination_collateral_vault.to_account_info(),
+                    collateral_token_program.to_account_info(),
+                )
+                .with_signer(&[pda_seeds.as_ref()]),
+        )?;
+    }
     Ok(())
 }
 
@@ -201,10 +725,18 @@ pub struct ChooseWinner<'info> {
         mut,
         seeds = [LOTTERY_INFO_STR.as_bytes(),],
         bump,
+        constraint = lottery_acct.queued_amount == 0 @ ErrorCode::WithdrawalsQueued,
     )]
     pub lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+        constraint = config.is_active(&lottery_acct, pause_flags::PAYOUT) @ ErrorCode::Paused,
+    )]
+    pub config: Box<Account<'info, ProgramConfig>>,
     #[account(mut, 
-        has_one = vrf @ ErrorCode::InvalidVrfAccount
+        has_one = vrf @ ErrorCode::InvalidVrfAccount,
+        constraint = state.key() == lottery_acct.vrf_client @ ErrorCode::InvalidVrfAccount,
     )]
     pub state: AccountLoader<'info, VrfClient>,
     #[account(
@@ -212,24 +744,471 @@ pub struct ChooseWinner<'info> {
             *vrf.to_account_info().owner == SWITCHBOARD_PROGRAM_ID @ ErrorCode::InvalidSwitchboardAccount
     )]
     pub vrf: AccountLoader<'info, VrfAccountData>,
+    #[account(
+        constraint = lottery_acct.is_operator(operator.key) @ ErrorCode::Unauthorized
+    )]
+    pub operator: Signer<'info>,
+    #[account(
+        constraint = source_liquidity_mint.key() == lottery_acct.liquidity_mint @ ErrorCode::InvalidMint,
+    )]
+    pub source_liquidity_mint: InterfaceAccount<'info, Mint>,
+    // checked against total principal to detect a shortfall
+    #[account(
+        mut,
+        associated_token::mint=source_liquidity_mint,
+        associated_token::authority=vault_signer,
+        associated_token::token_program=token_program,
+    )]
+    pub source_liquidity_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: may never have been created, checked and swept in the handler
+    #[account(
+        mut,
+        seeds = [PRIZE_VAULT_STR.as_bytes()],
+        bump,
+    )]
+    pub prize_vault: UncheckedAccount<'info>,
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump,
+        )]
+    pub vault_signer: AccountInfo<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+impl<'info> ChooseWinner<'info> {
+    pub fn transfer_prize_to_source_liquidity_vault(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let transfer_acct = TransferChecked {
+            to: self.source_liquidity_vault.to_account_info().clone(),
+            from: self.prize_vault.to_account_info().clone(),
+            mint: self.source_liquidity_mint.to_account_info().clone(),
+            authority: self.vault_signer.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), transfer_acct)
+    }
+}
+
+#[derive(Accounts)]
+pub struct FundPrize<'info> {
+    #[account(
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        constraint = lottery_acct.status == LotteryStatus::Started.to_code() @ ErrorCode::InvalidStatus,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        constraint = source_liquidity_mint.key() == lottery_acct.liquidity_mint @ ErrorCode::InvalidMint,
+    )]
+    source_liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = sponsor,
+        seeds = [PRIZE_VAULT_STR.as_bytes()],
+        bump,
+        token::mint=source_liquidity_mint,
+        token::authority=vault_signer,
+        token::token_program=token_program,
+    )]
+    prize_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint=source_liquidity_mint,
+        token::authority=sponsor,
+    )]
+    sponsor_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump,
+        )]
+    vault_signer: AccountInfo<'info>,
+    #[account(mut)]
+    sponsor: Signer<'info>,
+    token_program: Interface<'info, TokenInterface>,
+    system_program: Program<'info, System>,
+}
+impl<'info> FundPrize<'info> {
+    pub fn transfer_tokens_to_prize_vault(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let transfer_acct = TransferChecked {
+            to: self.prize_vault.to_account_info().clone(),
+            from: self.sponsor_token_account.to_account_info().clone(),
+            mint: self.source_liquidity_mint.to_account_info().clone(),
+            authority: self.sponsor.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), transfer_acct)
+    }
+}
+
+#[derive(Accounts)]
+pub struct WithdrawUserTokens<'info> {
+    #[account(
+        constraint = source_liquidity_mint.key() == lottery_acct.liquidity_mint @ ErrorCode::InvalidMint,
+    )]
+    source_liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint=source_liquidity_mint,
+        token::authority=user,
+    )]
+    user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint=source_liquidity_mint,
+        associated_token::authority=vault_signer,
+        associated_token::token_program=token_program,
+    )]
+    source_liquidity_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+   
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump,
+        )]
+    vault_signer: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+        constraint = config.is_active(&lottery_acct, pause_flags::PAYOUT) @ ErrorCode::Paused,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        mut,
+        token::mint=source_liquidity_mint,
+        constraint = treasury_token_account.owner == config.treasury @ ErrorCode::InvalidTreasury,
+    )]
+    treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    user: Signer<'info>,
+    token_program: Interface<'info, TokenInterface>,
+    associated_token_program: Program<'info, AssociatedToken>,
+    rent: Sysvar<'info, Rent>,
+    system_program: Program<'info, System>,
+}
+impl<'info> WithdrawUserTokens<'info> {
+    pub fn transfer_fee_to_treasury(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let transfer_acct = TransferChecked {
+            to: self.treasury_token_account.to_account_info().clone(),
+            from: self.source_liquidity_vault.to_account_info().clone(),
+            mint: self.source_liquidity_mint.to_account_info().clone(),
+            authority: self.vault_signer.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), transfer_acct)
+    }
+
+    pub fn transfer_tokens_from_source_liquidity_vault(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let transfer_acct = TransferChecked {
+            to: self.user_token_account.to_account_info().clone(),
+            from: self.source_liquidity_vault.to_account_info().clone(),
+            mint: self.source_liquidity_mint.to_account_info().clone(),
+            authority: self.vault_signer.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), transfer_acct)
+    }
+}
+#[derive(Accounts)]
+pub struct WithdrawUserTokensNative<'info> {
+    #[account(
+        address = native_mint::ID,
+        constraint = lottery_acct.liquidity_mint == native_mint::ID @ ErrorCode::InvalidMint,
+    )]
+    source_liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = user,
+        seeds = [UNWRAP_STR.as_bytes(), user.key().as_ref()],
+        bump,
+        token::mint=source_liquidity_mint,
+        token::authority=vault_signer,
+    )]
+    unwrap_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint=source_liquidity_mint,
+        associated_token::authority=vault_signer,
+        associated_token::token_program=token_program,
+    )]
+    source_liquidity_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump,
+        )]
+    vault_signer: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+        constraint = config.is_active(&lottery_acct, pause_flags::PAYOUT) @ ErrorCode::Paused,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        mut,
+        token::mint=source_liquidity_mint,
+        constraint = treasury_token_account.owner == config.treasury @ ErrorCode::InvalidTreasury,
+    )]
+    treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    user: Signer<'info>,
+    token_program: Interface<'info, TokenInterface>,
+    associated_token_program: Program<'info, AssociatedToken>,
+    rent: Sysvar<'info, Rent>,
+    system_program: Program<'info, System>,
+}
+impl<'info> WithdrawUserTokensNative<'info> {
+    pub fn transfer_fee_to_treasury(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let transfer_acct = TransferChecked {
+            to: self.treasury_token_account.to_account_info().clone(),
+            from: self.source_liquidity_vault.to_account_info().clone(),
+            mint: self.source_liquidity_mint.to_account_info().clone(),
+            authority: self.vault_signer.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), transfer_acct)
+    }
+
+    pub fn transfer_tokens_to_unwrap_account(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let transfer_acct = TransferChecked {
+            to: self.unwrap_account.to_account_info().clone(),
+            from: self.source_liquidity_vault.to_account_info().clone(),
+            mint: self.source_liquidity_mint.to_account_info().clone(),
+            authority: self.vault_signer.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), transfer_acct)
+    }
+
+    pub fn close_unwrap_account(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let close_acct = CloseAccount {
+            account: self.unwrap_account.to_account_info().clone(),
+            destination: self.user.to_account_info().clone(),
+            authority: self.vault_signer.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), close_acct)
+    }
 }
+#[derive(Accounts)]
+pub struct CancelLottery<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        constraint = lottery_acct.is_guardian(authority.key) @ ErrorCode::Unauthorized,
+        constraint = lottery_acct.is_cancellable() @ ErrorCode::InvalidStatus,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        mut,
+        seeds = [PROPOSAL_STR.as_bytes(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    proposal: Box<Account<'info, AdminProposal>>,
+    #[account(
+        seeds = [MULTISIG_STR.as_bytes(),],
+        bump = multisig.bump,
+    )]
+    multisig: Box<Account<'info, Multisig>>,
+    authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAccounts<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        close = payer,
+        constraint = lottery_acct.admin == user.key() @ ErrorCode::Unauthorized,
+        constraint = lottery_acct.is_closable() @ ErrorCode::OutstandingClaims,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+        constraint = config.is_active(&lottery_acct, pause_flags::PAYOUT) @ ErrorCode::Paused,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        mut,
+        address = lottery_acct.vrf_client @ ErrorCode::InvalidVrfAccount,
+        has_one = vrf @ ErrorCode::InvalidVrfAccount,
+        close = payer,
+    )]
+    state: AccountLoader<'info, VrfClient>,
+    #[account(
+
+        constraint = 
+            *vrf.to_account_info().owner == SWITCHBOARD_PROGRAM_ID @ ErrorCode::InvalidSwitchboardAccount,
+    )]
+    vrf: AccountLoader<'info, VrfAccountData>,
+    #[account(
+        mut,
+        seeds = [PROPOSAL_STR.as_bytes(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    proposal: Box<Account<'info, AdminProposal>>,
+    #[account(
+        seeds = [MULTISIG_STR.as_bytes(),],
+        bump = multisig.bump,
+    )]
+    multisig: Box<Account<'info, Multisig>>,
+    #[account(
+        constraint = source_liquidity_mint.key() == lottery_acct.liquidity_mint @ ErrorCode::InvalidMint,
+    )]
+    source_liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint=source_liquidity_mint,
+        associated_token::authority=vault_signer,
+        associated_token::token_program=token_program,
+    )]
+    source_liquidity_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    // required unless the lottery has no yield strategy
+    #[account(
+        constraint = destination_collateral_mint.key() == lottery_acct.lending.collateral_mint @ ErrorCode::InvalidStrategyAccounts,
+    )]
+    destination_collateral_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint=destination_collateral_mint,
+        associated_token::authority=vault_signer,
+        associated_token::token_program=collateral_token_program,
+    )]
+    destination_collateral_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// CHECK: may never have been created, checked and closed in the handler
+    #[account(
+        mut,
+        seeds = [PRIZE_VAULT_STR.as_bytes()],
+        bump,
+    )]
+    prize_vault: UncheckedAccount<'info>,
+    /// CHECK: may never have been created, checked and closed in the handler
+    #[account(
+        mut,
+        seeds = [REWARDS_VAULT_STR.as_bytes()],
+        bump,
+    )]
+    rewards_vault: UncheckedAccount<'info>,
+    /// CHECK: may never have been created, checked and closed in the handler
+    #[account(
+        mut,
+        seeds = [STAKING_INFO_STR.as_bytes()],
+        bump,
+    )]
+    staking_info: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        token::mint=source_liquidity_mint,
+        constraint = treasury_token_account.owner == config.treasury @ ErrorCode::InvalidTreasury,
+    )]
+    treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump,
+        )]
+    vault_signer: AccountInfo<'info>,
+    /// CHECK: original payer of the lottery accounts, receives the rent back
+    #[account(
+        mut,
+        address = lottery_acct.payer @ ErrorCode::InvalidPayer,
+    )]
+    payer: AccountInfo<'info>,
+    user: Signer<'info>,
+    token_program: Interface<'info, TokenInterface>,
+    collateral_token_program: Option<Program<'info, Token>>,
+}
+impl<'info> CloseAccounts<'info> {
+    pub fn sweep_dust_to_treasury(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let transfer_acct = TransferChecked {
+            to: self.treasury_token_account.to_account_info().clone(),
+            from: self.source_liquidity_vault.to_account_info().clone(),
+            mint: self.source_liquidity_mint.to_account_info().clone(),
+            authority: self.vault_signer.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), transfer_acct)
+    }
+
+    pub fn close_vault(
+        &self,
+        vault: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+    ) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let close_acct = CloseAccount {
+            account: vault,
+            destination: self.payer.to_account_info().clone(),
+            authority: self.vault_signer.to_account_info().clone(),
+        };
+        CpiContext::new(token_program, close_acct)
+    }
 
+    /// closes a vault the lottery owns if it was ever created, it has to be empty
+    pub fn close_empty_vault(
+        &self,
+        vault: &AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        if vault.data_is_empty() {
+            return Ok(());
+        }
+        let amount = InterfaceAccount::<TokenAccount>::try_from(vault)?.amount;
+        require!(amount == 0, ErrorCode::VaultNotEmpty);
+        let token_program = match &self.collateral_token_program {
+            _ if *vault.owner == self.token_program.key() => self.token_program.to_account_info(),
+            Some(collateral_token_program) if *vault.owner == collateral_token_program.key() => {
+                collateral_token_program.to_account_info()
+            }
+            _ => return Err(error!(ErrorCode::LendingAccountsRequired)),
+        };
+        token_interface::close_account(
+            self.close_vault(vault.clone(), token_program)
+                .with_signer(signer_seeds),
+        )
+    }
+}
 #[derive(Accounts)]
-pub struct WithdrawUserTokens<'info> {
-    source_liquidity_mint: Account<'info, Mint>,
+pub struct EnterLottery<'info> {
+    #[account(
+        constraint = source_liquidity_mint.key() == lottery_acct.liquidity_mint @ ErrorCode::InvalidMint,
+    )]
+    source_liquidity_mint: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
         token::mint=source_liquidity_mint,
         token::authority=user,
     )]
-    user_token_account: Box<Account<'info, TokenAccount>>,
+    user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut,
         associated_token::mint=source_liquidity_mint,
         associated_token::authority=vault_signer,
+        associated_token::token_program=token_program,
     )]
-    source_liquidity_vault: Box<Account<'info, TokenAccount>>,
-   
+    source_liquidity_vault: Box<InterfaceAccount<'info, TokenAccount>>,
     /// CHECK: This is the vault signer Acct
     #[account(
             seeds = [VAULT_SIGNER_STR.as_bytes()],
@@ -240,106 +1219,156 @@ pub struct WithdrawUserTokens<'info> {
         mut,
         seeds = [LOTTERY_INFO_STR.as_bytes(),],
         bump,
-
+        constraint = !lottery_acct.invest_on_entry @ ErrorCode::InvestOnEntry,
     )]
     lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+        constraint = config.is_active(&lottery_acct, pause_flags::ENTRY) @ ErrorCode::Paused,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
     #[account(mut)]
     user: Signer<'info>,
-    token_program: Program<'info, Token>,
+    token_program: Interface<'info, TokenInterface>,
     associated_token_program: Program<'info, AssociatedToken>,
     rent: Sysvar<'info, Rent>,
     system_program: Program<'info, System>,
 }
-impl<'info> WithdrawUserTokens<'info> {
-    pub fn transfer_tokens_from_source_liquidity_vault(
+
+impl<'info> EnterLottery<'info> {
+    pub fn transfer_tokens_to_source_liquidity_vault(
         &self,
-    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
-        let transfer_acct = Transfer {
-            to: self.user_token_account.to_account_info().clone(),
-            from: self.source_liquidity_vault.to_account_info().clone(),
-            authority: self.vault_signer.to_account_info().clone(),
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let transfer_acct = TransferChecked {
+            to: self.source_liquidity_vault.to_account_info().clone(),
+            from: self.user_token_account.to_account_info().clone(),
+            mint: self.source_liquidity_mint.to_account_info().clone(),
+            authority: self.user.to_account_info().clone(),
         };
         CpiContext::new(self.token_program.to_account_info(), transfer_acct)
     }
 }
+
 #[derive(Accounts)]
-pub struct CloseAccounts<'info> {
+pub struct EnterLotteryNative<'info> {
+    #[account(
+        address = native_mint::ID,
+        constraint = lottery_acct.liquidity_mint == native_mint::ID @ ErrorCode::InvalidMint,
+    )]
+    source_liquidity_mint: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
-        seeds = [LOTTERY_INFO_STR.as_bytes(),],
-        bump,
-        close = user,
+        associated_token::mint=source_liquidity_mint,
+        associated_token::authority=vault_signer,
+        associated_token::token_program=token_program,
     )]
-    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    source_liquidity_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump,
+        )]
+    vault_signer: AccountInfo<'info>,
     #[account(
         mut,
-        seeds = [
-            STATE_SEED, 
-            vrf.key().as_ref(),
-            user.key().as_ref(),
-        ],
-        close = user,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
         bump,
+        constraint = !lottery_acct.invest_on_entry @ ErrorCode::InvestOnEntry,
     )]
-    state: AccountLoader<'info, VrfClient>,
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
     #[account(
-
-        constraint = 
-            *vrf.to_account_info().owner == SWITCHBOARD_PROGRAM_ID @ ErrorCode::InvalidSwitchboardAccount,
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+        constraint = config.is_active(&lottery_acct, pause_flags::ENTRY) @ ErrorCode::Paused,
     )]
-    vrf: AccountLoader<'info, VrfAccountData>,
+    config: Box<Account<'info, ProgramConfig>>,
     #[account(mut)]
     user: Signer<'info>,
+    token_program: Interface<'info, TokenInterface>,
+    associated_token_program: Program<'info, AssociatedToken>,
+    system_program: Program<'info, System>,
+}
+
+impl<'info> EnterLotteryNative<'info> {
+    pub fn transfer_lamports_to_source_liquidity_vault(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, system_program::Transfer<'info>> {
+        let transfer_acct = system_program::Transfer {
+            from: self.user.to_account_info().clone(),
+            to: self.source_liquidity_vault.to_account_info().clone(),
+        };
+        CpiContext::new(self.system_program.to_account_info(), transfer_acct)
+    }
+
+    pub fn sync_source_liquidity_vault(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, SyncNative<'info>> {
+        let sync_acct = SyncNative {
+            account: self.source_liquidity_vault.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), sync_acct)
+    }
 }
+
 #[derive(Accounts)]
-pub struct EnterLottery<'info> {
-    source_liquidity_mint: Account<'info, Mint>,
+pub struct EnterLotteryInvested<'info> {
     #[account(
         mut,
-        token::mint=source_liquidity_mint,
-        token::authority=user,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        constraint = lottery_acct.invest_on_entry @ ErrorCode::NotInvestOnEntry,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+        constraint = config.is_active(&lottery_acct, pause_flags::ENTRY) @ ErrorCode::Paused,
+        constraint = config.is_active(&lottery_acct, pause_flags::LENDING_DEPOSIT) @ ErrorCode::Paused,
     )]
-    user_token_account: Box<Account<'info, TokenAccount>>,
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        constraint = source_liquidity_mint.key() == lottery_acct.liquidity_mint @ ErrorCode::InvalidMint,
+    )]
+    source_liquidity_mint: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
-        associated_token::mint=source_liquidity_mint,
-        associated_token::authority=vault_signer,
+        token::mint=source_liquidity_mint,
+        token::authority=user,
     )]
-    source_liquidity_vault: Box<Account<'info, TokenAccount>>,
-    destination_collateral_mint: Account<'info, Mint>,
+    user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut,
-        associated_token::mint=destination_collateral_mint,
-        associated_token::authority=vault_signer,
+        associated_token::mint=source_liquidity_mint,
+        associated_token::authority=authority,
+        associated_token::token_program=token_program,
     )]
-    destination_collateral_vault: Box<Account<'info, TokenAccount>>,
+    source_liquidity_vault: Box<InterfaceAccount<'info, TokenAccount>>,
     /// CHECK: This is the vault signer Acct
     #[account(
             seeds = [VAULT_SIGNER_STR.as_bytes()],
-            bump,
+            bump = lottery_acct.vault_signer_bump,
         )]
-    vault_signer: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
     #[account(
-        mut,
-        seeds = [LOTTERY_INFO_STR.as_bytes(),],
-        bump,
+        constraint = lending.belong_to(&lottery_acct.lending, authority.key) @ ErrorCode::InvalidStrategyAccounts,
+        constraint = lending.is_lending_program_allowed(&config) @ ErrorCode::LendingProgramNotAllowed,
+        constraint = lending.is_reserve_allowed(&config) @ ErrorCode::ReserveNotAllowed,
     )]
-    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    lending: LendingAccounts<'info>,
     #[account(mut)]
     user: Signer<'info>,
-    token_program: Program<'info, Token>,
-    associated_token_program: Program<'info, AssociatedToken>,
-    rent: Sysvar<'info, Rent>,
-    system_program: Program<'info, System>,
+    token_program: Interface<'info, TokenInterface>,
 }
 
-impl<'info> EnterLottery<'info> {
+impl<'info> EnterLotteryInvested<'info> {
     pub fn transfer_tokens_to_source_liquidity_vault(
         &self,
-    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
-        let transfer_acct = Transfer {
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let transfer_acct = TransferChecked {
             to: self.source_liquidity_vault.to_account_info().clone(),
             from: self.user_token_account.to_account_info().clone(),
+            mint: self.source_liquidity_mint.to_account_info().clone(),
             authority: self.user.to_account_info().clone(),
         };
         CpiContext::new(self.token_program.to_account_info(), transfer_acct)
@@ -347,126 +1376,338 @@ impl<'info> EnterLottery<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(vault_signer_bump: u8)]
-pub struct DepositTokensToLendingPool<'info> {
-    /// CHECK:
-    lending_program: AccountInfo<'info>,
-    source_liquidity_mint: Account<'info, Mint>,
+pub struct LeaveLottery<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        constraint = lottery_acct.status == LotteryStatus::Started.to_code() @ ErrorCode::InvalidStatus,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+        constraint = config.is_active(&lottery_acct, pause_flags::PAYOUT) @ ErrorCode::Paused,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        constraint = source_liquidity_mint.key() == lottery_acct.liquidity_mint @ ErrorCode::InvalidMint,
+    )]
+    source_liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint=source_liquidity_mint,
+        token::authority=user,
+    )]
+    user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut,
         associated_token::mint=source_liquidity_mint,
         associated_token::authority=authority,
+        associated_token::token_program=token_program,
+    )]
+    source_liquidity_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump = lottery_acct.vault_signer_bump,
+        )]
+    authority: AccountInfo<'info>,
+    // lending accounts, required when the lottery invests on entry
+    #[account(
+        constraint = lending.belong_to(&lottery_acct.lending, authority.key) @ ErrorCode::InvalidStrategyAccounts,
+    )]
+    lending: LendingAccounts<'info>,
+    user: Signer<'info>,
+    token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> LeaveLottery<'info> {
+    pub fn transfer_tokens_to_user(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let transfer_acct = TransferChecked {
+            to: self.user_token_account.to_account_info().clone(),
+            from: self.source_liquidity_vault.to_account_info().clone(),
+            mint: self.source_liquidity_mint.to_account_info().clone(),
+            authority: self.authority.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), transfer_acct)
+    }
+}
+
+#[derive(Accounts)]
+pub struct LockLottery<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        constraint = lottery_acct.status == LotteryStatus::Started.to_code() @ ErrorCode::InvalidStatus,
+        constraint = !lottery_acct.participants.is_empty() @ ErrorCode::ParticipantNotFound,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        constraint = lottery_acct.is_operator(operator.key) @ ErrorCode::Unauthorized
     )]
-    source_liquidity_vault: Box<Account<'info, TokenAccount>>,
-    destination_collateral_mint: Account<'info, Mint>,
+    operator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(allocation: u8)]
+pub struct DepositTokensToLendingPool<'info> {
     #[account(
         mut,
-        associated_token::mint=destination_collateral_mint,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        constraint = lottery_acct.status == LotteryStatus::Locked.to_code()
+            || lottery_acct.status == LotteryStatus::Invested.to_code() @ ErrorCode::InvalidStatus,
+        constraint = lottery_acct.has_allocation(allocation) @ ErrorCode::InvalidAllocation,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+        constraint = config.is_active(&lottery_acct, pause_flags::LENDING_DEPOSIT) @ ErrorCode::Paused,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        constraint = lottery_acct.is_operator(operator.key) @ ErrorCode::Unauthorized
+    )]
+    operator: Signer<'info>,
+    #[account(
+        constraint = source_liquidity_mint.key() == lottery_acct.liquidity_mint @ ErrorCode::InvalidMint,
+    )]
+    source_liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint=source_liquidity_mint,
         associated_token::authority=authority,
     )]
-    destination_collateral_vault: Box<Account<'info, TokenAccount>>,
+    source_liquidity_vault: Box<InterfaceAccount<'info, TokenAccount>>,
     /// CHECK: This is the vault signer Acct
     #[account(
             seeds = [VAULT_SIGNER_STR.as_bytes()],
-            bump = vault_signer_bump,
+            bump = lottery_acct.vault_signer_bump,
         )]
     authority: AccountInfo<'info>,
-    /// CHECK:
-    #[account(mut)]
-    reserve: AccountInfo<'info>,
-    /// CHECK:
-    #[account(mut)]
-    reserve_liquidity_supply: AccountInfo<'info>,
-    /// CHECK:
-    #[account(mut)]
-    reserve_collateral_mint: AccountInfo<'info>,
-    /// CHECK:
-    #[account(mut)]
-    reserve_liquidity_oracle: AccountInfo<'info>,
-    //CHECK:
-    // reserve_liquidity_oracle: AccountInfo<
-    /// CHECK:
-    lending_market: AccountInfo<'info>,
-    clock: Sysvar<'info, Clock>,
-    // token_program: Program<'info, Token>,
-    // rent: Sysvar<'info, Rent>,
-    // system_program: Program<'info, System>,
+    #[account(
+        constraint = lending.belong_to(&lottery_acct.allocation(allocation).lending, authority.key) @ ErrorCode::InvalidStrategyAccounts,
+        constraint = lending.is_lending_program_allowed(&config) @ ErrorCode::LendingProgramNotAllowed,
+        constraint = lending.is_reserve_allowed(&config) @ ErrorCode::ReserveNotAllowed,
+    )]
+    lending: LendingAccounts<'info>,
 }
 
 #[derive(Accounts)]
-#[instruction(vault_signer_bump: u8)]
-pub struct WithdrawTokensFromLendingPool<'info> {
-    /// CHECK:
-    lending_program: AccountInfo<'info>,
-    source_liquidity_mint: Account<'info, Mint>,
+#[instruction(allocation: u8)]
+pub struct RebalanceLiquidityBuffer<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        constraint = lottery_acct.is_rebalanceable() @ ErrorCode::InvalidStatus,
+        constraint = lottery_acct.has_allocation(allocation) @ ErrorCode::InvalidAllocation,
+        constraint = allocation != 0 || !lottery_acct.collateral_staked @ ErrorCode::CollateralStaked,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        constraint = lottery_acct.is_operator(operator.key) @ ErrorCode::Unauthorized
+    )]
+    operator: Signer<'info>,
+    #[account(
+        constraint = source_liquidity_mint.key() == lottery_acct.liquidity_mint @ ErrorCode::InvalidMint,
+    )]
+    source_liquidity_mint: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
         associated_token::mint=source_liquidity_mint,
         associated_token::authority=authority,
     )]
-    source_liquidity_vault: Box<Account<'info, TokenAccount>>,
-    destination_collateral_mint: Account<'info, Mint>,
+    source_liquidity_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump = lottery_acct.vault_signer_bump,
+        )]
+    authority: AccountInfo<'info>,
+    #[account(
+        constraint = lending.belong_to(&lottery_acct.allocation(allocation).lending, authority.key) @ ErrorCode::InvalidStrategyAccounts,
+        constraint = lending.is_lending_program_allowed(&config) @ ErrorCode::LendingProgramNotAllowed,
+        constraint = lending.is_reserve_allowed(&config) @ ErrorCode::ReserveNotAllowed,
+    )]
+    lending: LendingAccounts<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(allocation: u8)]
+pub struct HarvestYield<'info> {
     #[account(
         mut,
-        associated_token::mint=destination_collateral_mint,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        constraint = lottery_acct.is_rebalanceable() @ ErrorCode::InvalidStatus,
+        constraint = lottery_acct.has_allocation(allocation) @ ErrorCode::InvalidAllocation,
+        constraint = allocation != 0 || !lottery_acct.collateral_staked @ ErrorCode::CollateralStaked,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+        constraint = config.is_active(&lottery_acct, pause_flags::LENDING_WITHDRAW) @ ErrorCode::Paused,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        mut,
+        constraint = lottery_acct.is_operator(operator.key) @ ErrorCode::Unauthorized
+    )]
+    operator: Signer<'info>,
+    #[account(
+        constraint = source_liquidity_mint.key() == lottery_acct.liquidity_mint @ ErrorCode::InvalidMint,
+    )]
+    source_liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint=source_liquidity_mint,
         associated_token::authority=authority,
     )]
-    destination_collateral_vault: Box<Account<'info, TokenAccount>>,
+    source_liquidity_vault: Box<InterfaceAccount<'info, TokenAccount>>,
     /// CHECK: This is the vault signer Acct
     #[account(
             seeds = [VAULT_SIGNER_STR.as_bytes()],
-            bump = vault_signer_bump,
+            bump = lottery_acct.vault_signer_bump,
         )]
     authority: AccountInfo<'info>,
-    /// CHECK:
-    #[account(mut)]
-    reserve: AccountInfo<'info>,
-    /// CHECK:
-    #[account(mut)]
-    reserve_liquidity_supply: AccountInfo<'info>,
-    /// CHECK:
-    #[account(mut)]
-    reserve_collateral_mint: AccountInfo<'info>,
-    /// CHECK:
-    #[account(mut)]
-    reserve_liquidity_oracle: AccountInfo<'info>,
-    //CHECK:
-    // reserve_liquidity_oracle: AccountInfo<
-    /// CHECK:
-    lending_market: AccountInfo<'info>,
-    clock: Sysvar<'info, Clock>,
-    // token_program: Program<'info, Token>,
-    // rent: Sysvar<'info, Rent>,
-    // system_program: Program<'info, System>,
+    #[account(
+        constraint = lending.belong_to(&lottery_acct.allocation(allocation).lending, authority.key) @ ErrorCode::InvalidStrategyAccounts,
+        constraint = lending.is_lending_program_allowed(&config) @ ErrorCode::LendingProgramNotAllowed,
+        constraint = lending.is_reserve_allowed(&config) @ ErrorCode::ReserveNotAllowed,
+    )]
+    lending: LendingAccounts<'info>,
+    #[account(
+        init_if_needed,
+        payer = operator,
+        seeds = [PRIZE_VAULT_STR.as_bytes()],
+        bump,
+        token::mint=source_liquidity_mint,
+        token::authority=authority,
+        token::token_program=token_program,
+    )]
+    prize_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    token_program: Interface<'info, TokenInterface>,
+    system_program: Program<'info, System>,
+}
+
+impl<'info> HarvestYield<'info> {
+    pub fn transfer_yield_to_prize_vault(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let transfer_acct = TransferChecked {
+            to: self.prize_vault.to_account_info().clone(),
+            from: self.source_liquidity_vault.to_account_info().clone(),
+            mint: self.source_liquidity_mint.to_account_info().clone(),
+            authority: self.authority.to_account_info().clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), transfer_acct)
+    }
 }
 
-pub mod utils {
-    pub const VAULT_SIGNER_STR: &str = "vault_signer";
-    pub const LOTTERY_INFO_STR: &str = "lottery_info";
+#[derive(Accounts)]
+#[instruction(allocation: u8)]
+pub struct WithdrawTokensFromLendingPool<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        constraint = lottery_acct.status == LotteryStatus::Invested.to_code()
+            || lottery_acct.is_unwinding() @ ErrorCode::InvalidStatus,
+        constraint = lottery_acct.has_allocation(allocation) @ ErrorCode::InvalidAllocation,
+        constraint = allocation != 0 || !lottery_acct.collateral_staked @ ErrorCode::CollateralStaked,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+        constraint = config.is_active(&lottery_acct, pause_flags::LENDING_WITHDRAW) @ ErrorCode::Paused,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        constraint = lottery_acct.is_operator(operator.key) @ ErrorCode::Unauthorized
+    )]
+    operator: Signer<'info>,
+    #[account(
+        constraint = source_liquidity_mint.key() == lottery_acct.liquidity_mint @ ErrorCode::InvalidMint,
+    )]
+    source_liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint=source_liquidity_mint,
+        associated_token::authority=authority,
+    )]
+    source_liquidity_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump = lottery_acct.vault_signer_bump,
+        )]
+    authority: AccountInfo<'info>,
+    #[account(
+        constraint = lending.belong_to(&lottery_acct.allocation(allocation).lending, authority.key) @ ErrorCode::InvalidStrategyAccounts,
+        constraint = lending.is_lending_program_allowed(&config) @ ErrorCode::LendingProgramNotAllowed,
+        constraint = lending.is_reserve_allowed(&config) @ ErrorCode::ReserveNotAllowed,
+    )]
+    lending: LendingAccounts<'info>,
 }
+
 #[derive(Clone, AnchorSerialize, AnchorDeserialize)]
 pub struct InitializeLotteryParams {
     pub entry_fee: u64,
     pub max_participants: u64,
+    pub operator: Pubkey,
+    pub guardian: Pubkey,
+    // StrategyKind code the vault liquidity is invested through
+    pub strategy: u8,
+    // every entry is invested as it comes in
+    pub invest_on_entry: bool,
+    // share of the principal kept liquid in the source_liquidity_vault
+    pub buffer_bps: u16,
 }
 #[derive(Accounts)]
 pub struct InitializeLottery<'info> {
-    source_liquidity_mint: Account<'info, Mint>,
+    source_liquidity_mint: InterfaceAccount<'info, Mint>,
     #[account(
         init_if_needed,
         payer = user,
         associated_token::mint=source_liquidity_mint,
         associated_token::authority=vault_signer,
+        associated_token::token_program=token_program,
+    )]
+    source_liquidity_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    // lending side is left out for lotteries without a yield strategy
+    /// CHECK: checked against the config allow list, recorded on the lottery
+    #[account(
+        executable,
+        constraint = config.is_lending_program_allowed(lending_program.key) @ ErrorCode::LendingProgramNotAllowed,
+    )]
+    lending_program: Option<AccountInfo<'info>>,
+    /// CHECK: checked against the config allow list and parsed by the strategy
+    #[account(
+        constraint = config.is_reserve_allowed(reserve.key) @ ErrorCode::ReserveNotAllowed,
     )]
-    source_liquidity_vault: Box<Account<'info, TokenAccount>>,
-    destination_collateral_mint: Account<'info, Mint>,
+    reserve: Option<AccountInfo<'info>>,
+    destination_collateral_mint: Option<InterfaceAccount<'info, Mint>>,
     #[account(
         init_if_needed,
         payer = user,
         associated_token::mint=destination_collateral_mint,
         associated_token::authority=vault_signer,
+        associated_token::token_program=collateral_token_program,
     )]
-    destination_collateral_vault: Box<Account<'info, TokenAccount>>,
+    destination_collateral_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     /// CHECK: This is the vault signer Acct
     #[account(
             seeds = [VAULT_SIGNER_STR.as_bytes()],
@@ -481,6 +1722,12 @@ pub struct InitializeLottery<'info> {
         bump,
     )]
     lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+        constraint = config.pause_flags & pause_flags::ENTRY == 0 @ ErrorCode::Paused,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
     #[account(
         init,
         seeds = [
@@ -502,7 +1749,9 @@ pub struct InitializeLottery<'info> {
     authority: AccountInfo<'info>,
     #[account(mut)]
     user: Signer<'info>,
-    token_program: Program<'info, Token>,
+    token_program: Interface<'info, TokenInterface>,
+    /// lending collateral mints are always owned by the legacy token program
+    collateral_token_program: Option<Program<'info, Token>>,
     associated_token_program: Program<'info, AssociatedToken>,
     rent: Sysvar<'info, Rent>,
     #[account(address = solana_program::system_program::ID)]
@@ -515,10 +1764,45 @@ impl<'info> InitializeLottery<'info> {
         if params.max_participants > MAX_RESULT {
             return Err(error!(ErrorCode::MaxResultExceedsMaximum));
         }
+        // lotteries without a strategy never touch the collateral side
+        if StrategyKind::from(params.strategy)? != StrategyKind::None
+            && (self.lending_program.is_none()
+                || self.reserve.is_none()
+                || self.destination_collateral_vault.is_none()
+                || self.collateral_token_program.is_none())
+        {
+            return Err(error!(ErrorCode::LendingAccountsRequired));
+        }
+        if params.invest_on_entry && StrategyKind::from(params.strategy)? == StrategyKind::None {
+            return Err(error!(ErrorCode::StrategyDisabled));
+        }
 
         Ok(())
     }
 
+    /// reserve the strategy will invest into, checked against the vault mints
+    pub fn lending_reserve(&self, params: &InitializeLotteryParams) -> Result<Option<LendingReserve>> {
+        let strategy = StrategyKind::from(params.strategy)?;
+        if strategy == StrategyKind::None {
+            return Ok(None);
+        }
+        let lending_program = self.lending_program.as_ref().unwrap();
+        let reserve = self.reserve.as_ref().unwrap();
+        let reserve_info = strategy.reserve_info(lending_program.key, reserve)?;
+        require!(
+            reserve_info.liquidity_mint == self.source_liquidity_mint.key()
+                && Some(reserve_info.collateral_mint)
+                    == self.destination_collateral_mint.as_ref().map(|mint| mint.key()),
+            ErrorCode::InvalidStrategyAccounts,
+        );
+        Ok(Some(LendingReserve {
+            lending_program: lending_program.key(),
+            reserve: reserve.key(),
+            lending_market: reserve_info.lending_market,
+            collateral_mint: reserve_info.collateral_mint,
+        }))
+    }
+
     pub fn actuate(ctx: &Context<Self>, params: &InitializeLotteryParams) -> Result<()> {
         msg!("Actuate init");
 
@@ -528,6 +1812,9 @@ impl<'info> InitializeLottery<'info> {
         if vrf.authority != ctx.accounts.state.key() {
             return Err(error!(ErrorCode::InvalidAuthorityError));
         }
+        if !ctx.accounts.config.is_oracle_queue_allowed(&vrf.oracle_queue) {
+            return Err(error!(ErrorCode::OracleQueueNotAllowed));
+        }
         drop(vrf);
 
         msg!("Setting VrfClient state");
@@ -536,6 +1823,7 @@ impl<'info> InitializeLottery<'info> {
         state.bump = ctx.bumps.get("state").unwrap().clone();
         state.authority =  ctx.accounts.authority.key.clone();
         state.vrf = ctx.accounts.vrf.key();
+        state.version = VrfClient::LAYOUT_VERSION;
         
         msg!("Setting VrfClient max_result");
         if params.max_participants == 0 {
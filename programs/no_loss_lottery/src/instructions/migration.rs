@@ -0,0 +1,637 @@
+use std::mem;
+
+use crate::errors::ErrorCode;
+use crate::strategies::StrategyKind;
+use crate::{
+    utils::{CONFIG_STR, LOTTERY_INFO_STR, VAULT_SIGNER_STR},
+    AccountMigrated, Allocation, LendingReserve, LotteryInfo, LotteryStatus, Participant,
+    ProgramConfig, VrfClient,
+};
+use anchor_lang::{prelude::*, system_program, Discriminator};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::Token,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+/// LotteryInfo as written before layouts were versioned, frozen so those
+/// accounts are read with the layout they were created with
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyLotteryInfo {
+    pub winner: Option<Pubkey>,
+    pub entry_fee: u64,
+    pub participants: Vec<Pubkey>,
+    pub max_participants: u64,
+    pub status: u8,
+}
+
+impl LegacyLotteryInfo {
+    pub const MAX_SIZE: usize = 1 + 32 //winner
+    + 8
+    + 4 +( 32 * 5)
+    + 8
+    + 1;
+
+    /// version 0 accounts never grew past the legacy size, every versioned layout is larger
+    pub fn is_legacy(data: &[u8]) -> bool {
+        data.len() <= 8 + Self::MAX_SIZE
+    }
+
+    pub fn try_from_data(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() >= 8 && data[..8] == LotteryInfo::discriminator(),
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch,
+        );
+        Self::deserialize(&mut &data[8..])
+            .map_err(|_| error!(anchor_lang::error::ErrorCode::AccountDidNotDeserialize))
+    }
+
+    /// the legacy fields in the current layout, every entry paid the entry fee
+    /// and the lottery had no roles yet so `admin` runs all of them
+    pub fn upgrade(self, admin: Pubkey, guardian: Pubkey) -> LotteryInfo {
+        let participants: Vec<Participant> = self
+            .participants
+            .iter()
+            .map(|pubkey| Participant {
+                pubkey: *pubkey,
+                principal: self.entry_fee,
+            })
+            .collect();
+        LotteryInfo {
+            winner: self.winner,
+            entry_fee: self.entry_fee,
+            total_principal: self.entry_fee * participants.len() as u64,
+            participants,
+            max_participants: self.max_participants,
+            // Started and Completed kept their codes
+            status: self.status,
+            admin,
+            pending_admin: None,
+            operator: admin,
+            guardian,
+            pause_flags: 0,
+            vrf_client: Pubkey::default(),
+            version: LotteryInfo::LAYOUT_VERSION,
+            payer: Pubkey::default(),
+            // Port was the only lending market
+            strategy: 0,
+            liquidity_mint: Pubkey::default(),
+            lending: LendingReserve::default(),
+            vault_signer_bump: 0,
+            invested_amount: 0,
+            collateral_received: 0,
+            redeemed_amount: 0,
+            invest_on_entry: false,
+            buffer_bps: 0,
+            impaired: false,
+            settlement_liquidity: 0,
+            settlement_principal: 0,
+            invested_at: 0,
+            collateral_staked: false,
+            queued_amount: 0,
+            allocations: [Allocation::default(); LotteryInfo::EXTRA_ALLOCATIONS],
+            vrf_clients: [Pubkey::default(); LotteryInfo::MAX_VRF_CLIENTS],
+            locked_at: 0,
+            reserved: [0; 128],
+        }
+    }
+}
+
+/// values for fields older layouts lack, each one only lands where the account
+/// still holds the zeroed default
+pub struct Backfill {
+    pub payer: Pubkey,
+    pub vrf_client: Pubkey,
+    pub liquidity_mint: Pubkey,
+    pub vault_signer_bump: u8,
+    // None when no reserve accounts were passed
+    pub lending: Option<LendingReserve>,
+    // collateral a legacy lottery already holds, zero for versioned ones
+    pub collateral_amount: u64,
+    pub now: i64,
+}
+
+impl Backfill {
+    pub fn apply(&self, lottery: &mut LotteryInfo) -> Result<()> {
+        if lottery.payer == Pubkey::default() {
+            lottery.payer = self.payer;
+        }
+        if lottery.vrf_client == Pubkey::default() {
+            lottery.vrf_client = self.vrf_client;
+        }
+        if !lottery.is_vrf_client_registered(&lottery.vrf_client) {
+            lottery.register_vrf_client(lottery.vrf_client)?;
+        }
+        if lottery.liquidity_mint == Pubkey::default() {
+            lottery.liquidity_mint = self.liquidity_mint;
+        }
+        if lottery.vault_signer_bump == 0 {
+            lottery.vault_signer_bump = self.vault_signer_bump;
+        }
+        if StrategyKind::from(lottery.strategy)? != StrategyKind::None
+            && lottery.lending.reserve == Pubkey::default()
+        {
+            lottery.lending = self
+                .lending
+                .ok_or_else(|| error!(ErrorCode::MigrationBackfillRequired))?;
+        }
+        // legacy lotteries deposited into Port without recording it
+        if lottery.status == LotteryStatus::Started.to_code()
+            && self.collateral_amount > 0
+            && lottery.collateral_received == 0
+        {
+            lottery.status = LotteryStatus::Invested.to_code();
+            lottery.invested_amount = lottery.total_principal;
+            lottery.collateral_received = self.collateral_amount;
+            lottery.invested_at = self.now;
+        }
+        // entries closed before the lock time was recorded, the draw needs a fresh result
+        if lottery.locked_at == 0
+            && lottery.status != LotteryStatus::Started.to_code()
+            && lottery.is_pre_draw()
+        {
+            lottery.locked_at = self.now;
+        }
+        require!(
+            lottery.payer != Pubkey::default()
+                && lottery.vrf_client != Pubkey::default()
+                && lottery.liquidity_mint != Pubkey::default(),
+            ErrorCode::MigrationBackfillRequired,
+        );
+        Ok(())
+    }
+}
+
+/// grows `lottery_acct` to the current layout and stamps the new version.
+/// legacy accounts are read with their frozen layout and adopted by the config admin,
+/// versioned ones only grew at the end and are read with the current layout.
+/// fields the old layout lacked are backfilled from `params` and the passed accounts
+pub fn migrate_lottery(ctx: Context<MigrateLottery>, params: MigrateLotteryParams) -> Result<()> {
+    let lottery_info = ctx.accounts.lottery_acct.to_account_info();
+    let legacy = {
+        let data = lottery_info.try_borrow_data()?;
+        if LegacyLotteryInfo::is_legacy(&data) {
+            Some(LegacyLotteryInfo::try_from_data(&data)?)
+        } else {
+            None
+        }
+    };
+    realloc_account(
+        &lottery_info,
+        8 + LotteryInfo::MAX_SIZE,
+        &ctx.accounts.admin,
+        &ctx.accounts.system_program,
+    )?;
+
+    let (mut lottery, from_version) = match legacy {
+        Some(legacy) => {
+            // there was no lottery admin before versioning
+            require!(
+                ctx.accounts.config.admin == ctx.accounts.admin.key(),
+                ErrorCode::Unauthorized,
+            );
+            let lottery = legacy.upgrade(ctx.accounts.admin.key(), ctx.accounts.config.guardian);
+            (lottery, 0)
+        }
+        None => {
+            let mut lottery =
+                LotteryInfo::try_deserialize(&mut &lottery_info.try_borrow_data()?[..])?;
+            require!(
+                lottery.admin == ctx.accounts.admin.key(),
+                ErrorCode::Unauthorized,
+            );
+            let from_version = lottery.version;
+            require!(
+                from_version < LotteryInfo::LAYOUT_VERSION,
+                ErrorCode::AlreadyMigrated,
+            );
+            if from_version < 5 {
+                lottery = read_without_stale_padding(&lottery_info.try_borrow_data()?)?;
+                lottery.reserved = [0; 128];
+            }
+            if from_version < 2 {
+                // a shrunk participant list can leave stale bytes where the allocations now start
+                lottery.allocations = Default::default();
+            }
+            if from_version < 3 {
+                lottery.vrf_clients = [Pubkey::default(); LotteryInfo::MAX_VRF_CLIENTS];
+                lottery.vrf_clients[0] = lottery.vrf_client;
+            }
+            if from_version < 4 {
+                // same as the allocations, backfilled below
+                lottery.locked_at = 0;
+            }
+            lottery.version = LotteryInfo::LAYOUT_VERSION;
+            (lottery, from_version)
+        }
+    };
+
+    let strategy = StrategyKind::from(lottery.strategy)?;
+    Backfill {
+        payer: params.payer,
+        vrf_client: params.vrf_client,
+        liquidity_mint: ctx.accounts.source_liquidity_mint.key(),
+        vault_signer_bump: *ctx.bumps.get("vault_signer").unwrap(),
+        lending: ctx.accounts.lending_reserve(strategy)?,
+        collateral_amount: match (from_version, &ctx.accounts.destination_collateral_vault) {
+            (0, Some(vault)) => vault.amount,
+            _ => 0,
+        },
+        now: Clock::get()?.unix_timestamp,
+    }
+    .apply(&mut lottery)?;
+    // accounts the backfill was read from have to belong to the lottery
+    require!(
+        lottery.liquidity_mint == ctx.accounts.source_liquidity_mint.key(),
+        ErrorCode::InvalidMint,
+    );
+
+    let mut data = lottery_info.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data[..];
+    lottery.try_serialize(&mut writer)?;
+
+    emit!(AccountMigrated {
+        account: lottery_info.key(),
+        from_version,
+        to_version: LotteryInfo::LAYOUT_VERSION,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// layouts 1 to 4 kept a byte of padding between queued_amount and allocations,
+/// the fields after it are read again with that byte taken out
+fn read_without_stale_padding(data: &[u8]) -> Result<LotteryInfo> {
+    let lottery = LotteryInfo::try_deserialize(&mut &data[..])?;
+    let tail = Allocation::SIZE * LotteryInfo::EXTRA_ALLOCATIONS
+        + 32 * LotteryInfo::MAX_VRF_CLIENTS
+        + 8 //locked_at
+        + lottery.reserved.len();
+    let padding = 8 + lottery.try_to_vec()?.len() - tail;
+    let mut data = data.to_vec();
+    data.remove(padding);
+    LotteryInfo::try_deserialize(&mut &data[..])
+}
+
+/// same as migrate_lottery for a VrfClient, the lottery has to be migrated first
+pub fn migrate_vrf_client(ctx: Context<MigrateVrfClient>) -> Result<()> {
+    let state_info = ctx.accounts.state.to_account_info();
+    {
+        let data = state_info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == VrfClient::discriminator(),
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch,
+        );
+    }
+    realloc_account(
+        &state_info,
+        8 + mem::size_of::<VrfClient>(),
+        &ctx.accounts.admin,
+        &ctx.accounts.system_program,
+    )?;
+
+    let loader = AccountLoader::<VrfClient>::try_from(&state_info)?;
+    let mut state = loader.load_mut()?;
+    let from_version = state.version;
+    require!(
+        from_version < VrfClient::LAYOUT_VERSION,
+        ErrorCode::AlreadyMigrated,
+    );
+    state.version = VrfClient::LAYOUT_VERSION;
+
+    emit!(AccountMigrated {
+        account: state_info.key(),
+        from_version,
+        to_version: VrfClient::LAYOUT_VERSION,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+fn realloc_account<'info>(
+    account: &AccountInfo<'info>,
+    new_len: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    if account.data_len() >= new_len {
+        return Ok(());
+    }
+    let lamports_needed = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+    if lamports_needed > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            lamports_needed,
+        )?;
+    }
+    account.realloc(new_len, true)?;
+    Ok(())
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct MigrateLotteryParams {
+    // original payer of the lottery accounts, gets the rent back on close
+    pub payer: Pubkey,
+    // VrfClient the lottery draws with, lotteries from before versioning never recorded it
+    pub vrf_client: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct MigrateLottery<'info> {
+    /// CHECK: may still be in an older layout, deserialized after the realloc
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        owner = crate::ID,
+    )]
+    lottery_acct: UncheckedAccount<'info>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    source_liquidity_mint: InterfaceAccount<'info, Mint>,
+    // the vault has to exist for the mint the lottery is backfilled with
+    #[account(
+        associated_token::mint=source_liquidity_mint,
+        associated_token::authority=vault_signer,
+        associated_token::token_program=token_program,
+    )]
+    source_liquidity_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    // lending side, needed when a lottery with a strategy has no reserve recorded
+    /// CHECK: checked against the config allow list, recorded on the lottery
+    #[account(
+        executable,
+        constraint = config.is_lending_program_allowed(lending_program.key) @ ErrorCode::LendingProgramNotAllowed,
+    )]
+    lending_program: Option<AccountInfo<'info>>,
+    /// CHECK: checked against the config allow list and parsed by the strategy
+    #[account(
+        constraint = config.is_reserve_allowed(reserve.key) @ ErrorCode::ReserveNotAllowed,
+    )]
+    reserve: Option<AccountInfo<'info>>,
+    destination_collateral_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(
+        associated_token::mint=destination_collateral_mint,
+        associated_token::authority=vault_signer,
+        associated_token::token_program=collateral_token_program,
+    )]
+    destination_collateral_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump,
+        )]
+    vault_signer: AccountInfo<'info>,
+    #[account(mut)]
+    admin: Signer<'info>,
+    token_program: Interface<'info, TokenInterface>,
+    /// lending collateral mints are always owned by the legacy token program
+    collateral_token_program: Option<Program<'info, Token>>,
+    associated_token_program: Program<'info, AssociatedToken>,
+    system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateLottery<'info> {
+    /// reserve the lottery invests into, checked against the vault mints like on initialize
+    pub fn lending_reserve(&self, strategy: StrategyKind) -> Result<Option<LendingReserve>> {
+        let (lending_program, reserve) = match (&self.lending_program, &self.reserve) {
+            (Some(lending_program), Some(reserve)) if strategy != StrategyKind::None => {
+                (lending_program, reserve)
+            }
+            _ => return Ok(None),
+        };
+        let reserve_info = strategy.reserve_info(lending_program.key, reserve)?;
+        require!(
+            reserve_info.liquidity_mint == self.source_liquidity_mint.key()
+                && Some(reserve_info.collateral_mint)
+                    == self.destination_collateral_mint.as_ref().map(|mint| mint.key()),
+            ErrorCode::InvalidStrategyAccounts,
+        );
+        Ok(Some(LendingReserve {
+            lending_program: lending_program.key(),
+            reserve: reserve.key(),
+            lending_market: reserve_info.lending_market,
+            collateral_mint: reserve_info.collateral_mint,
+        }))
+    }
+}
+
+#[derive(Accounts)]
+pub struct MigrateVrfClient<'info> {
+    /// CHECK: may still be in an older layout, discriminator is checked in the handler
+    #[account(
+        mut,
+        owner = crate::ID,
+    )]
+    state: UncheckedAccount<'info>,
+    #[account(
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(mut)]
+    admin: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy_data(legacy: &LegacyLotteryInfo) -> Vec<u8> {
+        let mut data = LotteryInfo::discriminator().to_vec();
+        legacy.serialize(&mut data).unwrap();
+        data.resize(8 + LegacyLotteryInfo::MAX_SIZE, 0);
+        data
+    }
+
+    #[test]
+    fn upgrades_legacy_lottery_field_by_field() {
+        let participants = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let winner = participants[1];
+        let data = legacy_data(&LegacyLotteryInfo {
+            winner: Some(winner),
+            entry_fee: 50,
+            participants: participants.clone(),
+            max_participants: 5,
+            status: 1,
+        });
+        assert!(LegacyLotteryInfo::is_legacy(&data));
+
+        let admin = Pubkey::new_unique();
+        let guardian = Pubkey::new_unique();
+        let lottery = LegacyLotteryInfo::try_from_data(&data)
+            .unwrap()
+            .upgrade(admin, guardian);
+        assert_eq!(lottery.winner, Some(winner));
+        assert_eq!(lottery.entry_fee, 50);
+        assert_eq!(lottery.max_participants, 5);
+        assert_eq!(lottery.status, 1);
+        assert_eq!(lottery.total_principal, 100);
+        assert_eq!(lottery.participants.len(), 2);
+        assert_eq!(lottery.participants[0].pubkey, participants[0]);
+        assert_eq!(lottery.participants[1].principal, 50);
+        assert_eq!(lottery.admin, admin);
+        assert_eq!(lottery.operator, admin);
+        assert_eq!(lottery.guardian, guardian);
+        assert_eq!(lottery.version, LotteryInfo::LAYOUT_VERSION);
+    }
+
+    #[test]
+    fn rejects_legacy_data_of_another_account() {
+        let mut data = legacy_data(&LegacyLotteryInfo {
+            winner: None,
+            entry_fee: 1,
+            participants: vec![],
+            max_participants: 5,
+            status: 0,
+        });
+        data[0] ^= 1;
+        assert!(LegacyLotteryInfo::try_from_data(&data).is_err());
+    }
+
+    #[test]
+    fn drops_the_padding_byte_of_older_layouts() {
+        let mut lottery = legacy_lottery(2);
+        lottery.allocations[0].lending = reserve();
+        lottery.vrf_clients[1] = Pubkey::new_unique();
+        lottery.locked_at = 1_000;
+
+        // the same fields with the old byte sitting before allocations
+        let mut data = Vec::new();
+        lottery.try_serialize(&mut data).unwrap();
+        let tail = Allocation::SIZE * LotteryInfo::EXTRA_ALLOCATIONS
+            + 32 * LotteryInfo::MAX_VRF_CLIENTS
+            + 8
+            + 128;
+        let padding = data.len() - tail;
+        data.insert(padding, 0xff);
+        data.resize(8 + LotteryInfo::MAX_SIZE, 0);
+
+        let read = read_without_stale_padding(&data).unwrap();
+        assert_eq!(read.queued_amount, lottery.queued_amount);
+        assert_eq!(read.allocations[0].lending.reserve, lottery.allocations[0].lending.reserve);
+        assert_eq!(read.vrf_clients, lottery.vrf_clients);
+        assert_eq!(read.locked_at, 1_000);
+    }
+
+    fn backfill(lending: Option<LendingReserve>, collateral_amount: u64) -> Backfill {
+        Backfill {
+            payer: Pubkey::new_unique(),
+            vrf_client: Pubkey::new_unique(),
+            liquidity_mint: Pubkey::new_unique(),
+            vault_signer_bump: 254,
+            lending,
+            collateral_amount,
+            now: 1_000,
+        }
+    }
+
+    fn reserve() -> LendingReserve {
+        LendingReserve {
+            lending_program: Pubkey::new_unique(),
+            reserve: Pubkey::new_unique(),
+            lending_market: Pubkey::new_unique(),
+            collateral_mint: Pubkey::new_unique(),
+        }
+    }
+
+    fn legacy_lottery(status: u8) -> LotteryInfo {
+        LegacyLotteryInfo {
+            winner: None,
+            entry_fee: 10,
+            participants: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            max_participants: 5,
+            status,
+        }
+        .upgrade(Pubkey::new_unique(), Pubkey::new_unique())
+    }
+
+    #[test]
+    fn backfills_zeroed_fields() {
+        let mut lottery = legacy_lottery(0);
+        let lending = reserve();
+        let values = backfill(Some(lending), 0);
+        values.apply(&mut lottery).unwrap();
+
+        assert_eq!(lottery.payer, values.payer);
+        assert_eq!(lottery.vrf_client, values.vrf_client);
+        assert!(lottery.is_vrf_client_registered(&values.vrf_client));
+        assert_eq!(lottery.liquidity_mint, values.liquidity_mint);
+        assert_eq!(lottery.vault_signer_bump, 254);
+        assert_eq!(lottery.lending.reserve, lending.reserve);
+        assert_eq!(lottery.status, LotteryStatus::Started.to_code());
+        assert_eq!(lottery.locked_at, 0);
+    }
+
+    #[test]
+    fn backfill_keeps_fields_already_set() {
+        let mut lottery = legacy_lottery(0);
+        let payer = Pubkey::new_unique();
+        let liquidity_mint = Pubkey::new_unique();
+        let lending = reserve();
+        lottery.payer = payer;
+        lottery.liquidity_mint = liquidity_mint;
+        lottery.vault_signer_bump = 253;
+        lottery.lending = lending;
+        backfill(Some(reserve()), 0).apply(&mut lottery).unwrap();
+
+        assert_eq!(lottery.payer, payer);
+        assert_eq!(lottery.liquidity_mint, liquidity_mint);
+        assert_eq!(lottery.vault_signer_bump, 253);
+        assert_eq!(lottery.lending.reserve, lending.reserve);
+    }
+
+    #[test]
+    fn backfill_needs_a_reserve_for_a_lending_strategy() {
+        let mut lottery = legacy_lottery(0);
+        assert!(backfill(None, 0).apply(&mut lottery).is_err());
+
+        let mut lottery = legacy_lottery(0);
+        lottery.strategy = StrategyKind::None.to_code();
+        assert!(backfill(None, 0).apply(&mut lottery).is_ok());
+    }
+
+    #[test]
+    fn backfill_needs_a_payer() {
+        let mut lottery = legacy_lottery(0);
+        let mut values = backfill(Some(reserve()), 0);
+        values.payer = Pubkey::default();
+        assert!(values.apply(&mut lottery).is_err());
+    }
+
+    #[test]
+    fn backfill_records_collateral_a_legacy_lottery_holds() {
+        let mut lottery = legacy_lottery(0);
+        backfill(Some(reserve()), 90).apply(&mut lottery).unwrap();
+        assert_eq!(lottery.status, LotteryStatus::Invested.to_code());
+        assert_eq!(lottery.invested_amount, 20);
+        assert_eq!(lottery.collateral_received, 90);
+        assert_eq!(lottery.invested_at, 1_000);
+        assert_eq!(lottery.locked_at, 1_000);
+
+        // a drawn lottery already paid out of the vault
+        let mut lottery = legacy_lottery(1);
+        backfill(Some(reserve()), 90).apply(&mut lottery).unwrap();
+        assert_eq!(lottery.status, LotteryStatus::Completed.to_code());
+        assert_eq!(lottery.collateral_received, 0);
+    }
+
+    #[test]
+    fn versioned_layouts_are_not_legacy() {
+        assert!(!LegacyLotteryInfo::is_legacy(
+            &[0; 8 + LotteryInfo::MAX_SIZE]
+        ));
+    }
+}
@@ -1,5 +1,25 @@
+pub mod admin;
+pub mod config;
+pub mod emergency;
 pub mod lottery;
+pub mod migration;
+pub mod multisig;
+pub mod projection;
 pub mod randomness;
+pub mod staking;
+pub mod strategy;
+pub mod timelock;
+pub mod withdrawal_queue;
 
+pub use admin::*;
+pub use config::*;
+pub use emergency::*;
 pub use lottery::*;
-pub use randomness::*;
\ No newline at end of file
+pub use migration::*;
+pub use multisig::*;
+pub use projection::*;
+pub use randomness::*;
+pub use staking::*;
+pub use strategy::*;
+pub use timelock::*;
+pub use withdrawal_queue::*;
\ No newline at end of file
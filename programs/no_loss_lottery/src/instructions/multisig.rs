@@ -0,0 +1,140 @@
+use crate::errors::ErrorCode;
+use crate::{
+    utils::{CONFIG_STR, MULTISIG_STR, PROPOSAL_STR},
+    AdminAction, AdminProposal, Multisig, ProgramConfig, ProposalApproved, ProposalCreated,
+};
+use anchor_lang::prelude::*;
+
+pub fn create_multisig(
+    ctx: Context<CreateMultisig>,
+    signers: Vec<Pubkey>,
+    threshold: u8,
+) -> Result<()> {
+    let multisig = &mut ctx.accounts.multisig;
+    multisig.bump = *ctx.bumps.get("multisig").unwrap();
+    multisig.proposal_count = 0;
+    multisig.set_signers(signers, threshold)
+}
+
+pub fn create_proposal(ctx: Context<CreateProposal>, action: AdminAction) -> Result<()> {
+    let multisig = &mut ctx.accounts.multisig;
+    let proposer_index = multisig.signer_index(ctx.accounts.proposer.key)?;
+
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.bump = *ctx.bumps.get("proposal").unwrap();
+    proposal.id = multisig.proposal_count;
+    proposal.action = action;
+    proposal.proposer = ctx.accounts.proposer.key();
+    proposal.approvals = vec![false; multisig.signers.len()];
+    proposal.approvals[proposer_index] = true;
+    proposal.signer_set_seqno = multisig.signer_set_seqno;
+    proposal.executed = false;
+    multisig.proposal_count += 1;
+
+    emit!(ProposalCreated {
+        proposal: proposal.key(),
+        id: proposal.id,
+        proposer: proposal.proposer,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+    ctx.accounts
+        .proposal
+        .approve(&ctx.accounts.multisig, ctx.accounts.signer.key)?;
+
+    emit!(ProposalApproved {
+        proposal: ctx.accounts.proposal.key(),
+        signer: ctx.accounts.signer.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn set_signers(ctx: Context<SetSigners>, signers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+    ctx.accounts.proposal.consume(
+        &ctx.accounts.multisig,
+        &AdminAction::SetSigners {
+            signers: signers.clone(),
+            threshold,
+        },
+    )?;
+    ctx.accounts.multisig.set_signers(signers, threshold)
+}
+
+#[derive(Accounts)]
+pub struct CreateMultisig<'info> {
+    #[account(
+        init,
+        space = 8 + Multisig::MAX_SIZE,
+        payer = admin,
+        seeds = [MULTISIG_STR.as_bytes(),],
+        bump,
+    )]
+    multisig: Box<Account<'info, Multisig>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(mut)]
+    admin: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(
+        init,
+        space = 8 + AdminProposal::MAX_SIZE,
+        payer = proposer,
+        seeds = [PROPOSAL_STR.as_bytes(), multisig.proposal_count.to_le_bytes().as_ref()],
+        bump,
+    )]
+    proposal: Box<Account<'info, AdminProposal>>,
+    #[account(
+        mut,
+        seeds = [MULTISIG_STR.as_bytes(),],
+        bump = multisig.bump,
+    )]
+    multisig: Box<Account<'info, Multisig>>,
+    #[account(mut)]
+    proposer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    #[account(
+        mut,
+        seeds = [PROPOSAL_STR.as_bytes(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    proposal: Box<Account<'info, AdminProposal>>,
+    #[account(
+        seeds = [MULTISIG_STR.as_bytes(),],
+        bump = multisig.bump,
+    )]
+    multisig: Box<Account<'info, Multisig>>,
+    signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSigners<'info> {
+    #[account(
+        mut,
+        seeds = [PROPOSAL_STR.as_bytes(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    proposal: Box<Account<'info, AdminProposal>>,
+    #[account(
+        mut,
+        seeds = [MULTISIG_STR.as_bytes(),],
+        bump = multisig.bump,
+    )]
+    multisig: Box<Account<'info, Multisig>>,
+    executor: Signer<'info>,
+}
@@ -0,0 +1,165 @@
+use crate::errors::ErrorCode;
+use crate::strategies::StrategyKind;
+use crate::{
+    utils::{CONFIG_STR, LOTTERY_INFO_STR, PRIZE_VAULT_STR, STAKING_INFO_STR, VAULT_SIGNER_STR},
+    LotteryInfo, ProgramConfig, StakingInfo,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PrizeProjection {
+    pub total_principal: u64,
+    // owed to the withdrawal queue, held next to the principal but not part of it
+    pub queued_amount: u64,
+    // sitting in the liquidity vault
+    pub liquidity: u64,
+    // collateral of every allocation at the reserves' last exchange rate
+    pub collateral_value: u64,
+    // liquidity and collateral value on top of the principal and the queued withdrawals
+    pub accrued_yield: u64,
+    // prize vault balance, sponsored prizes and harvested yield
+    pub prize_vault: u64,
+    // accrued yield carried on at the same pace until `draw_time`, plus the prize vault
+    pub projected_prize: u64,
+    // protocol cut out of the projected prize
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+/// read-only view of the prize, meant to be simulated. reserves aren't refreshed so
+/// the exchange rate is whatever they last recorded. remaining accounts are a
+/// (reserve, collateral vault) pair for every allocation with a reserve, in slot order
+pub fn project_prize<'a, 'b, 'c, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, ProjectPrize<'info>>,
+    draw_time: i64,
+) -> Result<PrizeProjection> {
+    let lottery_acct = &ctx.accounts.lottery_acct;
+    let slots: Vec<_> = (0..=LotteryInfo::EXTRA_ALLOCATIONS as u8)
+        .map(|i| (i, lottery_acct.allocation(i)))
+        .filter(|(_, slot)| slot.is_set())
+        .collect();
+    require!(
+        ctx.remaining_accounts.len() == slots.len() * 2,
+        ErrorCode::InvalidStrategyAccounts,
+    );
+
+    let mut collateral_value = 0u64;
+    for ((index, slot), pair) in slots.iter().zip(ctx.remaining_accounts.chunks(2)) {
+        let (reserve, collateral_vault) = (&pair[0], &pair[1]);
+        require!(
+            reserve.key() == slot.lending.reserve,
+            ErrorCode::InvalidStrategyAccounts,
+        );
+        let collateral_vault = InterfaceAccount::<TokenAccount>::try_from(collateral_vault)?;
+        require!(
+            collateral_vault.mint == slot.lending.collateral_mint
+                && collateral_vault.owner == ctx.accounts.authority.key(),
+            ErrorCode::InvalidStrategyAccounts,
+        );
+        let reserve_info = StrategyKind::from(slot.strategy)?
+            .reserve_info(&slot.lending.lending_program, reserve)?;
+        // staked collateral of the primary reserve sits in the Port obligation
+        let staked = match &ctx.accounts.staking_info {
+            Some(staking_info) if *index == 0 => staking_info.staked_amount,
+            _ => 0,
+        };
+        collateral_value += reserve_info.collateral_value(collateral_vault.amount + staked);
+    }
+
+    let liquidity = ctx.accounts.source_liquidity_vault.amount;
+    let accrued_yield = accrued_yield(lottery_acct, liquidity + collateral_value);
+    let prize_vault = ctx
+        .accounts
+        .prize_vault
+        .as_ref()
+        .map_or(0, |prize_vault| prize_vault.amount);
+
+    // linear extrapolation of what the reserves made since the first deposit
+    let now = Clock::get()?.unix_timestamp;
+    let invested_at = lottery_acct.invested_at;
+    let projected_yield = if invested_at > 0 && now > invested_at && draw_time > now {
+        (accrued_yield as u128 * (draw_time - invested_at) as u128
+            / (now - invested_at) as u128) as u64
+    } else {
+        accrued_yield
+    };
+    let projected_prize = projected_yield + prize_vault;
+
+    Ok(PrizeProjection {
+        total_principal: lottery_acct.total_principal,
+        queued_amount: lottery_acct.queued_amount,
+        liquidity,
+        collateral_value,
+        accrued_yield,
+        prize_vault,
+        projected_prize,
+        fee: ctx.accounts.config.fee_on(projected_prize),
+        timestamp: now,
+    })
+}
+
+/// what `holdings` has on top of everything owed back, queued withdrawals are
+/// still paid out of the same vaults
+fn accrued_yield(lottery_acct: &LotteryInfo, holdings: u64) -> u64 {
+    holdings.saturating_sub(lottery_acct.total_principal + lottery_acct.queued_amount)
+}
+
+#[derive(Accounts)]
+pub struct ProjectPrize<'info> {
+    #[account(
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump = lottery_acct.vault_signer_bump,
+        )]
+    authority: AccountInfo<'info>,
+    #[account(
+        constraint = source_liquidity_mint.key() == lottery_acct.liquidity_mint @ ErrorCode::InvalidMint,
+    )]
+    source_liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        associated_token::mint=source_liquidity_mint,
+        associated_token::authority=authority,
+    )]
+    source_liquidity_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    // lotteries nobody funded or harvested into don't have one yet
+    #[account(
+        seeds = [PRIZE_VAULT_STR.as_bytes()],
+        bump,
+    )]
+    prize_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        seeds = [STAKING_INFO_STR.as_bytes(),],
+        bump = staking_info.bump,
+    )]
+    staking_info: Option<Box<Account<'info, StakingInfo>>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::lottery_info::tests::lottery;
+    use crate::LotteryStatus;
+
+    #[test]
+    fn queued_withdrawals_are_not_yield() {
+        let lottery_before = lottery(LotteryStatus::Started, &[100, 100]);
+        assert_eq!(accrued_yield(&lottery_before, 250), 50);
+
+        // one of them left through the queue and is still waiting on the vaults
+        let mut lottery = lottery(LotteryStatus::Started, &[100]);
+        lottery.queued_amount = 100;
+        assert_eq!(accrued_yield(&lottery, 250), 50);
+        assert_eq!(accrued_yield(&lottery, 150), 0);
+    }
+}
@@ -1,5 +1,5 @@
 
-use crate::{errors::ErrorCode, STATE_SEED, MAX_RESULT, VrfClient, RequestingRandomness, VrfClientResultUpdated, VrfClientInvoked,};
+use crate::{errors::ErrorCode, utils::{CONFIG_STR, LOTTERY_INFO_STR, MULTISIG_STR, PROPOSAL_STR, STATE_SEED, MAX_RESULT}, AdminAction, AdminProposal, LotteryInfo, LotteryStatus, Multisig, ProgramConfig, VrfClient, RequestingRandomness, VrfClientResultUpdated, VrfClientInvoked, VrfClientRegistered, VrfClientSelected,};
 
 use anchor_lang::{prelude::*, solana_program};
 use anchor_spl::token::TokenAccount;
@@ -25,6 +25,140 @@ pub fn request_result(ctx: Context<RequestResult>, params: RequestResultParams)
     RequestResult::actuate(&ctx, &params)
 }
 
+/// adds a VrfClient created with init_state to the ones the lottery can draw with
+pub fn register_vrf_client(ctx: Context<RegisterVrfClient>) -> Result<()> {
+    validate_vrf_client(
+        &ctx.accounts.lottery_acct,
+        &ctx.accounts.config,
+        &ctx.accounts.state,
+        &ctx.accounts.vrf,
+    )?;
+    let lottery_acct = &mut ctx.accounts.lottery_acct;
+    lottery_acct.register_vrf_client(ctx.accounts.state.key())?;
+
+    emit!(VrfClientRegistered {
+        lottery: lottery_acct.key(),
+        vrf_client: ctx.accounts.state.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// picks the registered VrfClient choose_winner reads the round's result from
+pub fn select_vrf_client(ctx: Context<SelectVrfClient>, vrf_client: Pubkey) -> Result<()> {
+    ctx.accounts
+        .proposal
+        .consume(&ctx.accounts.multisig, &AdminAction::SelectVrfClient { vrf_client })?;
+    require_no_pending_request(&ctx.accounts.lottery_acct, &ctx.accounts.current_state)?;
+    let lottery_acct = &mut ctx.accounts.lottery_acct;
+    require!(
+        lottery_acct.is_vrf_client_registered(&vrf_client),
+        ErrorCode::VrfClientNotRegistered,
+    );
+    lottery_acct.vrf_client = vrf_client;
+
+    emit!(VrfClientSelected {
+        lottery: lottery_acct.key(),
+        vrf_client,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// the client serving the round can only be swapped out before it was asked for a
+/// result, otherwise whoever swaps could drop a result they saw coming
+pub fn require_no_pending_request<'info>(
+    lottery_acct: &LotteryInfo,
+    current_state: &AccountLoader<'info, VrfClient>,
+) -> Result<()> {
+    require!(
+        current_state.key() == lottery_acct.vrf_client,
+        ErrorCode::InvalidVrfAccount,
+    );
+    require!(
+        !current_state.load()?.is_request_pending(),
+        ErrorCode::VrfRequestPending,
+    );
+    Ok(())
+}
+
+/// a VrfClient can draw for the lottery when its result indexes the participant list
+/// like the lottery's own client and it signs for a vrf on an allowed oracle queue
+pub fn validate_vrf_client<'info>(
+    lottery_acct: &LotteryInfo,
+    config: &ProgramConfig,
+    state: &AccountLoader<'info, VrfClient>,
+    vrf: &AccountLoader<'info, VrfAccountData>,
+) -> Result<()> {
+    require!(
+        *vrf.to_account_info().owner == SWITCHBOARD_PROGRAM_ID,
+        ErrorCode::InvalidSwitchboardAccount,
+    );
+    let client = state.load()?;
+    require!(client.vrf == vrf.key(), ErrorCode::InvalidVrfAccount);
+    require!(
+        client.max_result == lottery_acct.max_participants,
+        ErrorCode::InvalidVrfAccount,
+    );
+    drop(client);
+
+    let vrf_data = vrf.load()?;
+    require!(
+        vrf_data.authority == state.key(),
+        ErrorCode::InvalidAuthorityError,
+    );
+    require!(
+        config.is_oracle_queue_allowed(&vrf_data.oracle_queue),
+        ErrorCode::OracleQueueNotAllowed,
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RegisterVrfClient<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+        constraint = lottery_acct.is_pre_draw() @ ErrorCode::InvalidStatus,
+    )]
+    pub lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, ProgramConfig>>,
+    // checked by validate_vrf_client
+    pub state: AccountLoader<'info, VrfClient>,
+    pub vrf: AccountLoader<'info, VrfAccountData>,
+    pub admin: Signer<'info>,
+}
+
+// switching once entries close would let the signers pick among results
+#[derive(Accounts)]
+pub struct SelectVrfClient<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        constraint = lottery_acct.status == LotteryStatus::Started.to_code() @ ErrorCode::InvalidStatus,
+    )]
+    pub lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        mut,
+        seeds = [PROPOSAL_STR.as_bytes(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Box<Account<'info, AdminProposal>>,
+    #[account(
+        seeds = [MULTISIG_STR.as_bytes(),],
+        bump = multisig.bump,
+    )]
+    pub multisig: Box<Account<'info, Multisig>>,
+    // the client serving the round, checked by require_no_pending_request
+    pub current_state: AccountLoader<'info, VrfClient>,
+}
 
 #[derive(Accounts)]
 #[instruction(params: InitStateParams)]
@@ -88,6 +222,7 @@ impl InitState<'_> {
         state.bump = ctx.bumps.get("state").unwrap().clone();
         state.authority =  ctx.accounts.authority.key.clone();
         state.vrf = ctx.accounts.vrf.key();
+        state.version = VrfClient::LAYOUT_VERSION;
         
         msg!("Setting VrfClient max_result");
         if params.max_result == 0 {
@@ -131,7 +266,8 @@ pub struct RequestResult<'info> {
         has_one = data_buffer,
         constraint = 
             oracle_queue.load()?.authority == queue_authority.key()
-            && *oracle_queue.to_account_info().owner == SWITCHBOARD_PROGRAM_ID @ ErrorCode::InvalidSwitchboardAccount
+            && *oracle_queue.to_account_info().owner == SWITCHBOARD_PROGRAM_ID @ ErrorCode::InvalidSwitchboardAccount,
+        constraint = config.is_oracle_queue_allowed(&oracle_queue.key()) @ ErrorCode::OracleQueueNotAllowed,
     )]
     pub oracle_queue: AccountLoader<'info, OracleQueueAccountData>,
     /// CHECK: Will be checked in the CPI instruction
@@ -185,6 +321,11 @@ pub struct RequestResult<'info> {
     pub recent_blockhashes: AccountInfo<'info>,
     #[account(address = anchor_spl::token::ID)]
     pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, ProgramConfig>>,
 }
 
 #[derive(Clone, AnchorSerialize, AnchorDeserialize)]
@@ -244,6 +385,9 @@ impl RequestResult<'_> {
 
         let mut client_state = ctx.accounts.state.load_mut()?;
         client_state.result = 0;
+        // a result relayed earlier in the same second can't pass for this request's
+        client_state.result_buffer = [0u8; 32];
+        client_state.requested_at = clock::Clock::get()?.unix_timestamp;
 
         emit!(RequestingRandomness{
             vrf_client: ctx.accounts.state.key(),
@@ -308,18 +452,17 @@ impl UpdateResult<'_> {
         
       
 
-        if state.result != result {
-            state.result_buffer = result_buffer;
-            state.result = result;
-            state.last_timestamp = clock.unix_timestamp;
-
-            emit!(VrfClientResultUpdated {
-                vrf_client: ctx.accounts.state.key(),
-                result: state.result,
-                result_buffer: result_buffer,
-                timestamp: clock.unix_timestamp,
-            });
-        }
+        // a new buffer is a new fulfillment even when it reduces to the same result
+        state.result_buffer = result_buffer;
+        state.result = result;
+        state.last_timestamp = clock.unix_timestamp;
+
+        emit!(VrfClientResultUpdated {
+            vrf_client: ctx.accounts.state.key(),
+            result: state.result,
+            result_buffer: result_buffer,
+            timestamp: clock.unix_timestamp,
+        });
 
         Ok(())
     }
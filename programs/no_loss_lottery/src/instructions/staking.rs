@@ -0,0 +1,483 @@
+use crate::errors::ErrorCode;
+use crate::strategies::port_staking::{self, PortStakingAccounts};
+use crate::strategies::StrategyKind;
+use crate::{
+    pause_flags,
+    utils::{CONFIG_STR, LOTTERY_INFO_STR, REWARDS_VAULT_STR, STAKING_INFO_STR, VAULT_SIGNER_STR},
+    CollateralStaked, CollateralUnstaked, LotteryInfo, LotteryStatus, ProgramConfig,
+    RewardPrizePaid, RewardsClaimed, StakingInfo,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::Token,
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+/// sets up the vault signer's Port obligation and stake account, both have to be
+/// allocated (owned by the lending and staking programs) earlier in the transaction
+pub fn init_port_staking(ctx: Context<InitPortStaking>) -> Result<()> {
+    let pda_seeds = &[
+        VAULT_SIGNER_STR.as_bytes(),
+        &[ctx.accounts.lottery_acct.vault_signer_bump],
+    ];
+    port_staking::init_obligation(
+        &ctx.accounts.lending_program,
+        &ctx.accounts.obligation,
+        &ctx.accounts.lending_market,
+        &ctx.accounts.authority,
+        &ctx.accounts.clock.to_account_info(),
+        &ctx.accounts.rent.to_account_info(),
+        &ctx.accounts.token_program.to_account_info(),
+        &[pda_seeds.as_ref()],
+    )?;
+    port_staking::create_stake_account(
+        &ctx.accounts.staking_program,
+        &ctx.accounts.stake_account,
+        &ctx.accounts.staking_pool,
+        &ctx.accounts.authority,
+        &ctx.accounts.rent.to_account_info(),
+        &[pda_seeds.as_ref()],
+    )?;
+
+    let staking_info = &mut ctx.accounts.staking_info;
+    staking_info.bump = *ctx.bumps.get("staking_info").unwrap();
+    staking_info.staking_program = ctx.accounts.staking_program.key();
+    staking_info.staking_pool = ctx.accounts.staking_pool.key();
+    staking_info.stake_account = ctx.accounts.stake_account.key();
+    staking_info.obligation = ctx.accounts.obligation.key();
+    staking_info.reward_mint = ctx.accounts.reward_mint.key();
+    staking_info.staked_amount = 0;
+    Ok(())
+}
+
+/// stakes all collateral in the primary reserve's vault, it has to be unstaked
+/// again before anything redeems it
+pub fn stake_collateral(ctx: Context<ManageStakedCollateral>) -> Result<()> {
+    require!(ctx.accounts.lottery_acct.is_rebalanceable(), ErrorCode::InvalidStatus);
+    require!(
+        ctx.accounts.config.is_active(&ctx.accounts.lottery_acct, pause_flags::LENDING_DEPOSIT),
+        ErrorCode::Paused,
+    );
+    let collateral_amount = ctx.accounts.destination_collateral_vault.amount;
+    require!(collateral_amount > 0, ErrorCode::NothingToStake);
+
+    let accounts = ctx.accounts.staking_accounts();
+    accounts.validate()?;
+    accounts.refresh()?;
+    let pda_seeds = &[
+        VAULT_SIGNER_STR.as_bytes(),
+        &[ctx.accounts.lottery_acct.vault_signer_bump],
+    ];
+    accounts.stake(collateral_amount, &[pda_seeds.as_ref()])?;
+
+    ctx.accounts.staking_info.staked_amount += collateral_amount;
+    let lottery_acct = &mut ctx.accounts.lottery_acct;
+    lottery_acct.collateral_staked = true;
+
+    emit!(CollateralStaked {
+        lottery: lottery_acct.key(),
+        collateral_amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// moves everything staked back into the primary reserve's collateral vault
+pub fn unstake_collateral(ctx: Context<ManageStakedCollateral>) -> Result<()> {
+    require!(
+        ctx.accounts.config.is_active(&ctx.accounts.lottery_acct, pause_flags::LENDING_WITHDRAW),
+        ErrorCode::Paused,
+    );
+    let collateral_amount = ctx.accounts.staking_info.staked_amount;
+    require!(collateral_amount > 0, ErrorCode::NothingToStake);
+
+    let accounts = ctx.accounts.staking_accounts();
+    accounts.validate()?;
+    accounts.refresh()?;
+    let pda_seeds = &[
+        VAULT_SIGNER_STR.as_bytes(),
+        &[ctx.accounts.lottery_acct.vault_signer_bump],
+    ];
+    accounts.unstake(collateral_amount, &[pda_seeds.as_ref()])?;
+
+    ctx.accounts.staking_info.staked_amount = 0;
+    let lottery_acct = &mut ctx.accounts.lottery_acct;
+    lottery_acct.collateral_staked = false;
+
+    emit!(CollateralUnstaked {
+        lottery: lottery_acct.key(),
+        collateral_amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// collects the staking rewards into the rewards vault, they go to the winner on top of the prize
+pub fn claim_rewards(ctx: Context<ClaimStakingRewards>) -> Result<()> {
+    let rewards_before = ctx.accounts.rewards_vault.amount;
+    let pda_seeds = &[
+        VAULT_SIGNER_STR.as_bytes(),
+        &[ctx.accounts.lottery_acct.vault_signer_bump],
+    ];
+    port_staking::claim_reward(
+        &ctx.accounts.staking_program,
+        &ctx.accounts.stake_account,
+        &ctx.accounts.staking_pool,
+        &ctx.accounts.reward_token_pool,
+        &ctx.accounts.rewards_vault.to_account_info(),
+        &ctx.accounts.staking_program_authority,
+        &ctx.accounts.authority,
+        &ctx.accounts.clock.to_account_info(),
+        &ctx.accounts.token_program.to_account_info(),
+        &[pda_seeds.as_ref()],
+    )?;
+    ctx.accounts.rewards_vault.reload()?;
+    let amount = ctx.accounts.rewards_vault.amount - rewards_before;
+
+    emit!(RewardsClaimed {
+        lottery: ctx.accounts.lottery_acct.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// pays the winner everything in the rewards vault. nothing is staked anymore once the
+/// round is drawn, so rewards claim_rewards collects afterwards were still earned by
+/// this round's collateral and go to its winner too
+pub fn claim_reward_prize(ctx: Context<ClaimRewardPrize>) -> Result<()> {
+    let amount = ctx.accounts.rewards_vault.amount;
+    require!(amount > 0, ErrorCode::NothingToClaim);
+    let pda_seeds = &[
+        VAULT_SIGNER_STR.as_bytes(),
+        &[ctx.accounts.lottery_acct.vault_signer_bump],
+    ];
+    token_interface::transfer_checked(
+        ctx.accounts.transfer_rewards_to_winner().with_signer(&[pda_seeds.as_ref()]),
+        amount,
+        ctx.accounts.reward_mint.decimals,
+    )?;
+
+    emit!(RewardPrizePaid {
+        lottery: ctx.accounts.lottery_acct.key(),
+        winner: ctx.accounts.winner.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitPortStaking<'info> {
+    #[account(
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+        constraint = lottery_acct.strategy == StrategyKind::Port.to_code() @ ErrorCode::InvalidStrategy,
+        constraint = lottery_acct.is_pre_draw() @ ErrorCode::InvalidStatus,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + StakingInfo::MAX_SIZE,
+        seeds = [STAKING_INFO_STR.as_bytes(),],
+        bump,
+    )]
+    staking_info: Box<Account<'info, StakingInfo>>,
+    #[account(mut)]
+    admin: Signer<'info>,
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump = lottery_acct.vault_signer_bump,
+        )]
+    authority: AccountInfo<'info>,
+    /// CHECK: checked against the lottery's reserve
+    #[account(
+        constraint = lending_program.key() == lottery_acct.lending.lending_program @ ErrorCode::InvalidStrategyAccounts,
+    )]
+    lending_program: AccountInfo<'info>,
+    /// CHECK: checked against the lottery's reserve
+    #[account(
+        constraint = lending_market.key() == lottery_acct.lending.lending_market @ ErrorCode::InvalidStrategyAccounts,
+    )]
+    lending_market: AccountInfo<'info>,
+    /// CHECK: allocated for the lending program, initialized by it
+    #[account(
+        mut,
+        owner = lending_program.key() @ ErrorCode::InvalidStakingAccounts,
+    )]
+    obligation: AccountInfo<'info>,
+    /// CHECK: checked against the config allow list
+    #[account(
+        executable,
+        constraint = config.is_staking_program_allowed(staking_program.key) @ ErrorCode::StakingProgramNotAllowed,
+    )]
+    staking_program: AccountInfo<'info>,
+    /// CHECK: has to be the reserve's staking pool, Port checks it on every deposit
+    #[account(
+        owner = staking_program.key() @ ErrorCode::InvalidStakingAccounts,
+    )]
+    staking_pool: AccountInfo<'info>,
+    /// CHECK: allocated for the staking program, initialized by it
+    #[account(
+        mut,
+        owner = staking_program.key() @ ErrorCode::InvalidStakingAccounts,
+    )]
+    stake_account: AccountInfo<'info>,
+    reward_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [REWARDS_VAULT_STR.as_bytes()],
+        bump,
+        token::mint=reward_mint,
+        token::authority=authority,
+        token::token_program=token_program,
+    )]
+    rewards_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    clock: Sysvar<'info, Clock>,
+    rent: Sysvar<'info, Rent>,
+    /// Port only works with the legacy token program
+    token_program: Program<'info, Token>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageStakedCollateral<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        // a cancelled lottery unstakes so the collateral can be redeemed
+        constraint = lottery_acct.is_rebalanceable()
+            || lottery_acct.is_unwinding() @ ErrorCode::InvalidStatus,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        mut,
+        seeds = [STAKING_INFO_STR.as_bytes(),],
+        bump = staking_info.bump,
+    )]
+    staking_info: Box<Account<'info, StakingInfo>>,
+    #[account(
+        constraint = lottery_acct.is_operator(operator.key) @ ErrorCode::Unauthorized
+    )]
+    operator: Signer<'info>,
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump = lottery_acct.vault_signer_bump,
+        )]
+    authority: AccountInfo<'info>,
+    /// CHECK: checked against the lottery's reserve
+    #[account(
+        constraint = lending_program.key() == lottery_acct.lending.lending_program @ ErrorCode::InvalidStrategyAccounts,
+    )]
+    lending_program: AccountInfo<'info>,
+    /// CHECK: checked against the lottery's reserve
+    #[account(
+        mut,
+        constraint = reserve.key() == lottery_acct.lending.reserve @ ErrorCode::InvalidStrategyAccounts,
+    )]
+    reserve: AccountInfo<'info>,
+    /// CHECK: checked against the reserve data by staking_accounts().validate()
+    reserve_liquidity_oracle: AccountInfo<'info>,
+    /// CHECK: checked against the reserve data by staking_accounts().validate()
+    #[account(mut)]
+    reserve_collateral_supply: AccountInfo<'info>,
+    /// CHECK: checked against the lottery's reserve
+    #[account(
+        constraint = lending_market.key() == lottery_acct.lending.lending_market @ ErrorCode::InvalidStrategyAccounts,
+    )]
+    lending_market: AccountInfo<'info>,
+    /// CHECK: derived and checked by Port
+    lending_market_authority: AccountInfo<'info>,
+    /// CHECK: checked against the staking position
+    #[account(
+        mut,
+        constraint = obligation.key() == staking_info.obligation @ ErrorCode::InvalidStakingAccounts,
+    )]
+    obligation: AccountInfo<'info>,
+    /// CHECK: checked against the staking position
+    #[account(
+        mut,
+        constraint = stake_account.key() == staking_info.stake_account @ ErrorCode::InvalidStakingAccounts,
+    )]
+    stake_account: AccountInfo<'info>,
+    /// CHECK: checked against the staking position
+    #[account(
+        mut,
+        constraint = staking_pool.key() == staking_info.staking_pool @ ErrorCode::InvalidStakingAccounts,
+    )]
+    staking_pool: AccountInfo<'info>,
+    /// CHECK: checked against the staking position
+    #[account(
+        constraint = staking_program.key() == staking_info.staking_program @ ErrorCode::InvalidStakingAccounts,
+    )]
+    staking_program: AccountInfo<'info>,
+    #[account(
+        constraint = destination_collateral_mint.key() == lottery_acct.lending.collateral_mint @ ErrorCode::InvalidStrategyAccounts,
+    )]
+    destination_collateral_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint=destination_collateral_mint,
+        associated_token::authority=authority,
+    )]
+    destination_collateral_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    clock: Sysvar<'info, Clock>,
+    token_program: Program<'info, Token>,
+}
+
+impl<'info> ManageStakedCollateral<'info> {
+    pub fn staking_accounts(&self) -> PortStakingAccounts<'info> {
+        PortStakingAccounts {
+            lending_program: self.lending_program.to_account_info(),
+            staking_program: self.staking_program.to_account_info(),
+            reserve: self.reserve.to_account_info(),
+            reserve_liquidity_oracle: self.reserve_liquidity_oracle.to_account_info(),
+            reserve_collateral_supply: self.reserve_collateral_supply.to_account_info(),
+            lending_market: self.lending_market.to_account_info(),
+            lending_market_authority: self.lending_market_authority.to_account_info(),
+            obligation: self.obligation.to_account_info(),
+            stake_account: self.stake_account.to_account_info(),
+            staking_pool: self.staking_pool.to_account_info(),
+            collateral_vault: self.destination_collateral_vault.to_account_info(),
+            authority: self.authority.to_account_info(),
+            clock: self.clock.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClaimStakingRewards<'info> {
+    #[account(
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [STAKING_INFO_STR.as_bytes(),],
+        bump = staking_info.bump,
+    )]
+    staking_info: Box<Account<'info, StakingInfo>>,
+    #[account(
+        constraint = lottery_acct.is_operator(operator.key) @ ErrorCode::Unauthorized
+    )]
+    operator: Signer<'info>,
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump = lottery_acct.vault_signer_bump,
+        )]
+    authority: AccountInfo<'info>,
+    /// CHECK: checked against the staking position
+    #[account(
+        mut,
+        constraint = stake_account.key() == staking_info.stake_account @ ErrorCode::InvalidStakingAccounts,
+    )]
+    stake_account: AccountInfo<'info>,
+    /// CHECK: checked against the staking position
+    #[account(
+        mut,
+        constraint = staking_pool.key() == staking_info.staking_pool @ ErrorCode::InvalidStakingAccounts,
+    )]
+    staking_pool: AccountInfo<'info>,
+    /// CHECK: checked against the staking pool by the staking program
+    #[account(mut)]
+    reward_token_pool: AccountInfo<'info>,
+    /// CHECK: derived and checked by the staking program
+    staking_program_authority: AccountInfo<'info>,
+    /// CHECK: checked against the staking position
+    #[account(
+        constraint = staking_program.key() == staking_info.staking_program @ ErrorCode::InvalidStakingAccounts,
+    )]
+    staking_program: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [REWARDS_VAULT_STR.as_bytes()],
+        bump,
+    )]
+    rewards_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    clock: Sysvar<'info, Clock>,
+    token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewardPrize<'info> {
+    #[account(
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        constraint = lottery_acct.status == LotteryStatus::Completed.to_code()
+            || lottery_acct.status == LotteryStatus::Settled.to_code() @ ErrorCode::LotteryStillOn,
+        constraint = lottery_acct.winner == Some(winner.key()) @ ErrorCode::Unauthorized,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+        constraint = config.is_active(&lottery_acct, pause_flags::PAYOUT) @ ErrorCode::Paused,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        seeds = [STAKING_INFO_STR.as_bytes(),],
+        bump = staking_info.bump,
+        constraint = staking_info.staked_amount == 0 @ ErrorCode::CollateralStaked,
+    )]
+    staking_info: Box<Account<'info, StakingInfo>>,
+    #[account(mut)]
+    winner: Signer<'info>,
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump = lottery_acct.vault_signer_bump,
+        )]
+    authority: AccountInfo<'info>,
+    #[account(
+        constraint = reward_mint.key() == staking_info.reward_mint @ ErrorCode::InvalidMint,
+    )]
+    reward_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [REWARDS_VAULT_STR.as_bytes()],
+        bump,
+    )]
+    rewards_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = winner,
+        associated_token::mint=reward_mint,
+        associated_token::authority=winner,
+        associated_token::token_program=token_program,
+    )]
+    winner_reward_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    token_program: Interface<'info, TokenInterface>,
+    associated_token_program: Program<'info, AssociatedToken>,
+    system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimRewardPrize<'info> {
+    pub fn transfer_rewards_to_winner(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let transfer_acct = TransferChecked {
+            from: self.rewards_vault.to_account_info(),
+            mint: self.reward_mint.to_account_info(),
+            to: self.winner_reward_account.to_account_info(),
+            authority: self.authority.to_account_info(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), transfer_acct)
+    }
+}
@@ -0,0 +1,546 @@
+use crate::errors::ErrorCode;
+use crate::strategies::{StrategyAccounts, StrategyKind};
+use crate::{
+    pause_flags,
+    utils::{CONFIG_STR, LOTTERY_INFO_STR, MAX_BPS, MULTISIG_STR, PROPOSAL_STR, VAULT_SIGNER_STR},
+    AdminAction, AdminProposal, Allocation, AllocationUpdated, LendingReserve, LotteryInfo,
+    Multisig, ProgramConfig, StrategyMigrated,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address, AssociatedToken},
+    token::{accessor, Token},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+/// moves everything invested in the lottery's reserve into a new reserve or strategy
+/// in one go, the first `old_remaining_accounts` remaining accounts go to the current
+/// strategy and the rest to the new one. only a lottery that already invests can migrate,
+/// one initialized without a strategy has no reserve or collateral vault to move from
+/// and keeps its liquidity in the vault for good
+pub fn migrate_strategy<'a, 'b, 'c, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, MigrateStrategy<'info>>,
+    new_strategy: u8,
+    old_remaining_accounts: u8,
+) -> Result<()> {
+    ctx.accounts.proposal.consume(
+        &ctx.accounts.multisig,
+        &AdminAction::MigrateStrategy {
+            strategy: new_strategy,
+            lending_program: ctx.accounts.new_lending_program.key(),
+            reserve: ctx.accounts.new_reserve.key(),
+        },
+    )?;
+    let old_strategy = StrategyKind::from(ctx.accounts.lottery_acct.strategy)?;
+    let new_strategy = StrategyKind::from(new_strategy)?;
+    require!(new_strategy != StrategyKind::None, ErrorCode::StrategyDisabled);
+    require!(
+        old_remaining_accounts as usize <= ctx.remaining_accounts.len(),
+        ErrorCode::InvalidStrategyAccounts,
+    );
+    let (old_remaining, new_remaining) =
+        ctx.remaining_accounts.split_at(old_remaining_accounts as usize);
+
+    let new_accounts = ctx.accounts.new_strategy_accounts(new_remaining);
+    let reserve_info =
+        new_strategy.reserve_info(new_accounts.lending_program.key, &new_accounts.reserve)?;
+    require!(
+        reserve_info.liquidity_mint == ctx.accounts.lottery_acct.liquidity_mint,
+        ErrorCode::InvalidMint,
+    );
+    // the primary can't share a reserve or collateral vault with an extra allocation
+    require!(
+        !ctx.accounts.lottery_acct.is_reserve_used(
+            0,
+            ctx.accounts.new_reserve.key,
+            &reserve_info.collateral_mint,
+        ),
+        ErrorCode::DuplicateReserve,
+    );
+    new_strategy.validate(&new_accounts)?;
+
+    let pda_seeds = &[
+        VAULT_SIGNER_STR.as_bytes(),
+        &[ctx.accounts.lottery_acct.vault_signer_bump],
+    ];
+    let collateral_amount = ctx.accounts.destination_collateral_vault.amount;
+    let mut liquidity_amount = 0;
+    let mut new_collateral_amount = 0;
+    if collateral_amount > 0 {
+        let old_accounts = ctx.accounts.old_strategy_accounts(old_remaining);
+        old_strategy.validate(&old_accounts)?;
+        old_strategy.refresh(&old_accounts)?;
+
+        let liquidity_before = ctx.accounts.source_liquidity_vault.amount;
+        old_strategy.redeem(&old_accounts, collateral_amount, &[pda_seeds.as_ref()])?;
+        ctx.accounts.source_liquidity_vault.reload()?;
+        liquidity_amount = ctx.accounts.source_liquidity_vault.amount - liquidity_before;
+
+        new_strategy.refresh(&new_accounts)?;
+        let collateral_before = ctx.accounts.new_destination_collateral_vault.amount;
+        new_strategy.deposit(&new_accounts, liquidity_amount, &[pda_seeds.as_ref()])?;
+        ctx.accounts.new_destination_collateral_vault.reload()?;
+        new_collateral_amount =
+            ctx.accounts.new_destination_collateral_vault.amount - collateral_before;
+    }
+
+    // invested_amount keeps tracking principal so harvest still sees the yield
+    let lottery_acct = &mut ctx.accounts.lottery_acct;
+    let old_reserve = lottery_acct.lending.reserve;
+    lottery_acct.strategy = new_strategy.to_code();
+    lottery_acct.lending = LendingReserve {
+        lending_program: ctx.accounts.new_lending_program.key(),
+        reserve: ctx.accounts.new_reserve.key(),
+        lending_market: reserve_info.lending_market,
+        collateral_mint: reserve_info.collateral_mint,
+    };
+    lottery_acct.collateral_received = new_collateral_amount;
+
+    emit!(StrategyMigrated {
+        lottery: lottery_acct.key(),
+        old_reserve,
+        new_reserve: lottery_acct.lending.reserve,
+        strategy: lottery_acct.strategy,
+        liquidity_amount,
+        collateral_amount: new_collateral_amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// points an extra allocation slot at a reserve with the given weight, a slot can only
+/// move to another reserve once rebalance has emptied it (weight 0 drains it).
+/// every reserve has its own slot since slots sharing a collateral vault can't be told apart
+pub fn set_allocation(
+    ctx: Context<SetAllocation>,
+    index: u8,
+    strategy: u8,
+    weight_bps: u16,
+) -> Result<()> {
+    ctx.accounts.proposal.consume(
+        &ctx.accounts.multisig,
+        &AdminAction::SetAllocation {
+            index,
+            strategy,
+            reserve: ctx.accounts.reserve.key(),
+            weight_bps,
+        },
+    )?;
+    require!(
+        index >= 1 && index as usize <= LotteryInfo::EXTRA_ALLOCATIONS,
+        ErrorCode::InvalidAllocation,
+    );
+    let lottery_acct = &ctx.accounts.lottery_acct;
+    require!(
+        StrategyKind::from(lottery_acct.strategy)? != StrategyKind::None,
+        ErrorCode::StrategyDisabled,
+    );
+    let strategy = StrategyKind::from(strategy)?;
+    require!(strategy != StrategyKind::None, ErrorCode::StrategyDisabled);
+
+    let reserve_info =
+        strategy.reserve_info(ctx.accounts.lending_program.key, &ctx.accounts.reserve)?;
+    require!(
+        reserve_info.liquidity_mint == lottery_acct.liquidity_mint,
+        ErrorCode::InvalidMint,
+    );
+    require!(
+        reserve_info.collateral_mint == ctx.accounts.destination_collateral_mint.key(),
+        ErrorCode::InvalidStrategyAccounts,
+    );
+    require!(
+        !lottery_acct.is_reserve_used(
+            index,
+            ctx.accounts.reserve.key,
+            &reserve_info.collateral_mint,
+        ),
+        ErrorCode::DuplicateReserve,
+    );
+
+    let mut slot = lottery_acct.allocation(index);
+    if slot.lending.reserve != ctx.accounts.reserve.key() {
+        require!(slot.collateral_received == 0, ErrorCode::AllocationNotEmpty);
+        slot = Allocation::default();
+    }
+    let other_weights: u64 = lottery_acct
+        .allocations
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i + 1 != index as usize)
+        .map(|(_, a)| a.weight_bps as u64)
+        .sum();
+    require!(
+        other_weights + weight_bps as u64 <= MAX_BPS,
+        ErrorCode::InvalidAllocationWeights,
+    );
+
+    slot.strategy = strategy.to_code();
+    slot.lending = LendingReserve {
+        lending_program: ctx.accounts.lending_program.key(),
+        reserve: ctx.accounts.reserve.key(),
+        lending_market: reserve_info.lending_market,
+        collateral_mint: reserve_info.collateral_mint,
+    };
+    slot.weight_bps = weight_bps;
+    let lottery_acct = &mut ctx.accounts.lottery_acct;
+    lottery_acct.set_allocation(index, slot);
+
+    emit!(AllocationUpdated {
+        lottery: lottery_acct.key(),
+        index,
+        reserve: slot.lending.reserve,
+        weight_bps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetAllocation<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        constraint = lottery_acct.is_pre_draw() @ ErrorCode::InvalidStatus,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        mut,
+        seeds = [PROPOSAL_STR.as_bytes(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    proposal: Box<Account<'info, AdminProposal>>,
+    #[account(
+        seeds = [MULTISIG_STR.as_bytes(),],
+        bump = multisig.bump,
+    )]
+    multisig: Box<Account<'info, Multisig>>,
+    // pays for the collateral vault of a new reserve
+    #[account(mut)]
+    payer: Signer<'info>,
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump = lottery_acct.vault_signer_bump,
+        )]
+    authority: AccountInfo<'info>,
+    /// CHECK: checked against the config allow list
+    #[account(
+        executable,
+        constraint = config.is_lending_program_allowed(lending_program.key) @ ErrorCode::LendingProgramNotAllowed,
+    )]
+    lending_program: AccountInfo<'info>,
+    /// CHECK: checked against the config allow list and parsed by the strategy
+    #[account(
+        constraint = config.is_reserve_allowed(reserve.key) @ ErrorCode::ReserveNotAllowed,
+    )]
+    reserve: AccountInfo<'info>,
+    destination_collateral_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint=destination_collateral_mint,
+        associated_token::authority=authority,
+        associated_token::token_program=collateral_token_program,
+    )]
+    destination_collateral_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    collateral_token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateStrategy<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        constraint = lottery_acct.is_pre_draw() @ ErrorCode::InvalidStatus,
+        constraint = !lottery_acct.collateral_staked @ ErrorCode::CollateralStaked,
+        constraint = lottery_acct.strategy != StrategyKind::None.to_code() @ ErrorCode::NothingToMigrate,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+        constraint = config.is_active(&lottery_acct, pause_flags::LENDING_WITHDRAW) @ ErrorCode::Paused,
+        constraint = config.is_active(&lottery_acct, pause_flags::LENDING_DEPOSIT) @ ErrorCode::Paused,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        mut,
+        seeds = [PROPOSAL_STR.as_bytes(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    proposal: Box<Account<'info, AdminProposal>>,
+    #[account(
+        seeds = [MULTISIG_STR.as_bytes(),],
+        bump = multisig.bump,
+    )]
+    multisig: Box<Account<'info, Multisig>>,
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump = lottery_acct.vault_signer_bump,
+        )]
+    authority: AccountInfo<'info>,
+    #[account(
+        constraint = source_liquidity_mint.key() == lottery_acct.liquidity_mint @ ErrorCode::InvalidMint,
+    )]
+    source_liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint=source_liquidity_mint,
+        associated_token::authority=authority,
+        associated_token::token_program=token_program,
+    )]
+    source_liquidity_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // current reserve
+    /// CHECK: checked against the lottery's reserve
+    #[account(
+        constraint = lending_program.key() == lottery_acct.lending.lending_program @ ErrorCode::InvalidStrategyAccounts,
+    )]
+    lending_program: AccountInfo<'info>,
+    #[account(
+        constraint = destination_collateral_mint.key() == lottery_acct.lending.collateral_mint @ ErrorCode::InvalidStrategyAccounts,
+    )]
+    destination_collateral_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint=destination_collateral_mint,
+        associated_token::authority=authority,
+        associated_token::token_program=collateral_token_program,
+    )]
+    destination_collateral_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: checked against the lottery's reserve
+    #[account(
+        mut,
+        constraint = reserve.key() == lottery_acct.lending.reserve @ ErrorCode::InvalidStrategyAccounts,
+    )]
+    reserve: AccountInfo<'info>,
+    /// CHECK: checked against the reserve data by the strategy
+    #[account(mut)]
+    reserve_liquidity_supply: AccountInfo<'info>,
+    /// CHECK: checked against the reserve data by the strategy
+    #[account(mut)]
+    reserve_collateral_mint: AccountInfo<'info>,
+    /// CHECK: checked against the reserve data by the strategy
+    #[account(mut)]
+    reserve_liquidity_oracle: AccountInfo<'info>,
+    /// CHECK: checked against the lottery's reserve
+    #[account(
+        constraint = lending_market.key() == lottery_acct.lending.lending_market @ ErrorCode::InvalidStrategyAccounts,
+    )]
+    lending_market: AccountInfo<'info>,
+
+    // new reserve
+    /// CHECK: checked against the config allow list
+    #[account(
+        executable,
+        constraint = config.is_lending_program_allowed(new_lending_program.key) @ ErrorCode::LendingProgramNotAllowed,
+    )]
+    new_lending_program: AccountInfo<'info>,
+    new_destination_collateral_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = executor,
+        associated_token::mint=new_destination_collateral_mint,
+        associated_token::authority=authority,
+        associated_token::token_program=collateral_token_program,
+    )]
+    new_destination_collateral_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: checked against the config allow list and parsed by the strategy
+    #[account(
+        mut,
+        constraint = config.is_reserve_allowed(new_reserve.key) @ ErrorCode::ReserveNotAllowed,
+    )]
+    new_reserve: AccountInfo<'info>,
+    /// CHECK: checked against the reserve data by the strategy
+    #[account(mut)]
+    new_reserve_liquidity_supply: AccountInfo<'info>,
+    /// CHECK: checked against the reserve data by the strategy
+    #[account(mut)]
+    new_reserve_collateral_mint: AccountInfo<'info>,
+    /// CHECK: checked against the reserve data by the strategy
+    #[account(mut)]
+    new_reserve_liquidity_oracle: AccountInfo<'info>,
+    /// CHECK: checked against the reserve data by the strategy
+    new_lending_market: AccountInfo<'info>,
+
+    clock: Sysvar<'info, Clock>,
+    #[account(mut)]
+    executor: Signer<'info>,
+    token_program: Interface<'info, TokenInterface>,
+    /// lending collateral mints are always owned by the legacy token program
+    collateral_token_program: Program<'info, Token>,
+    associated_token_program: Program<'info, AssociatedToken>,
+    system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateStrategy<'info> {
+    pub fn old_strategy_accounts<'a>(
+        &self,
+        remaining_accounts: &'a [AccountInfo<'info>],
+    ) -> StrategyAccounts<'a, 'info> {
+        StrategyAccounts {
+            lending_program: self.lending_program.to_account_info(),
+            source_liquidity_mint: self.source_liquidity_mint.to_account_info(),
+            source_liquidity_vault: self.source_liquidity_vault.to_account_info(),
+            destination_collateral_mint: self.destination_collateral_mint.to_account_info(),
+            destination_collateral_vault: self.destination_collateral_vault.to_account_info(),
+            authority: self.authority.to_account_info(),
+            reserve: self.reserve.to_account_info(),
+            reserve_liquidity_supply: self.reserve_liquidity_supply.to_account_info(),
+            reserve_collateral_mint: self.reserve_collateral_mint.to_account_info(),
+            reserve_liquidity_oracle: self.reserve_liquidity_oracle.to_account_info(),
+            lending_market: self.lending_market.to_account_info(),
+            clock: self.clock.to_account_info(),
+            remaining_accounts,
+        }
+    }
+
+    pub fn new_strategy_accounts<'a>(
+        &self,
+        remaining_accounts: &'a [AccountInfo<'info>],
+    ) -> StrategyAccounts<'a, 'info> {
+        StrategyAccounts {
+            lending_program: self.new_lending_program.to_account_info(),
+            source_liquidity_mint: self.source_liquidity_mint.to_account_info(),
+            source_liquidity_vault: self.source_liquidity_vault.to_account_info(),
+            destination_collateral_mint: self.new_destination_collateral_mint.to_account_info(),
+            destination_collateral_vault: self.new_destination_collateral_vault.to_account_info(),
+            authority: self.authority.to_account_info(),
+            reserve: self.new_reserve.to_account_info(),
+            reserve_liquidity_supply: self.new_reserve_liquidity_supply.to_account_info(),
+            reserve_collateral_mint: self.new_reserve_collateral_mint.to_account_info(),
+            reserve_liquidity_oracle: self.new_reserve_liquidity_oracle.to_account_info(),
+            lending_market: self.new_lending_market.to_account_info(),
+            clock: self.clock.to_account_info(),
+            remaining_accounts,
+        }
+    }
+}
+
+/// the lending accounts of one allocation slot, shared by every instruction that
+/// moves liquidity in or out of a reserve. they are checked against the slot with
+/// `belong_to`, instructions that only sometimes reach the reserve can leave them out
+#[derive(Accounts)]
+pub struct LendingAccounts<'info> {
+    /// CHECK: checked against the lottery's reserve
+    #[account(executable)]
+    pub lending_program: Option<AccountInfo<'info>>,
+    pub destination_collateral_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub destination_collateral_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// CHECK: checked against the lottery's reserve
+    #[account(mut)]
+    pub reserve: Option<AccountInfo<'info>>,
+    /// CHECK: checked against the reserve data by the strategy
+    #[account(mut)]
+    pub reserve_liquidity_supply: Option<AccountInfo<'info>>,
+    /// CHECK: checked against the reserve data by the strategy
+    #[account(mut)]
+    pub reserve_collateral_mint: Option<AccountInfo<'info>>,
+    /// CHECK: checked against the reserve data by the strategy
+    #[account(mut)]
+    pub reserve_liquidity_oracle: Option<AccountInfo<'info>>,
+    /// CHECK: checked against the lottery's reserve
+    pub lending_market: Option<AccountInfo<'info>>,
+    pub clock: Option<Sysvar<'info, Clock>>,
+}
+
+impl<'info> LendingAccounts<'info> {
+    /// whichever accounts were passed are the ones of `lending`, the collateral vault
+    /// being the one `authority` holds
+    pub fn belong_to(&self, lending: &LendingReserve, authority: &Pubkey) -> bool {
+        fn is(account: Option<Pubkey>, key: Pubkey) -> bool {
+            account.map_or(true, |account| account == key)
+        }
+        is(self.lending_program.as_ref().map(|a| a.key()), lending.lending_program)
+            && is(
+                self.destination_collateral_mint.as_ref().map(|a| a.key()),
+                lending.collateral_mint,
+            )
+            && is(
+                self.destination_collateral_vault.as_ref().map(|a| a.key()),
+                get_associated_token_address(authority, &lending.collateral_mint),
+            )
+            && is(self.reserve.as_ref().map(|a| a.key()), lending.reserve)
+            && is(self.lending_market.as_ref().map(|a| a.key()), lending.lending_market)
+    }
+
+    pub fn is_lending_program_allowed(&self, config: &ProgramConfig) -> bool {
+        self.lending_program
+            .as_ref()
+            .map_or(true, |lending_program| config.is_lending_program_allowed(lending_program.key))
+    }
+
+    pub fn is_reserve_allowed(&self, config: &ProgramConfig) -> bool {
+        self.reserve
+            .as_ref()
+            .map_or(true, |reserve| config.is_reserve_allowed(reserve.key))
+    }
+
+    /// collateral the slot's vault holds right now, read from the account so it is
+    /// current after a strategy CPI
+    pub fn collateral_amount(&self) -> Result<u64> {
+        let vault = self
+            .destination_collateral_vault
+            .as_ref()
+            .ok_or_else(|| error!(ErrorCode::LendingAccountsRequired))?;
+        accessor::amount(&vault.to_account_info())
+    }
+
+    pub fn strategy_accounts<'a>(
+        &self,
+        source_liquidity_mint: &InterfaceAccount<'info, Mint>,
+        source_liquidity_vault: &InterfaceAccount<'info, TokenAccount>,
+        authority: &AccountInfo<'info>,
+        remaining_accounts: &'a [AccountInfo<'info>],
+    ) -> Result<StrategyAccounts<'a, 'info>> {
+        let (
+            Some(lending_program),
+            Some(destination_collateral_mint),
+            Some(destination_collateral_vault),
+            Some(reserve),
+            Some(reserve_liquidity_supply),
+            Some(reserve_collateral_mint),
+            Some(reserve_liquidity_oracle),
+            Some(lending_market),
+            Some(clock),
+        ) = (
+            &self.lending_program,
+            &self.destination_collateral_mint,
+            &self.destination_collateral_vault,
+            &self.reserve,
+            &self.reserve_liquidity_supply,
+            &self.reserve_collateral_mint,
+            &self.reserve_liquidity_oracle,
+            &self.lending_market,
+            &self.clock,
+        ) else {
+            return Err(error!(ErrorCode::LendingAccountsRequired));
+        };
+        Ok(StrategyAccounts {
+            lending_program: lending_program.to_account_info(),
+            source_liquidity_mint: source_liquidity_mint.to_account_info(),
+            source_liquidity_vault: source_liquidity_vault.to_account_info(),
+            destination_collateral_mint: destination_collateral_mint.to_account_info(),
+            destination_collateral_vault: destination_collateral_vault.to_account_info(),
+            authority: authority.to_account_info(),
+            reserve: reserve.to_account_info(),
+            reserve_liquidity_supply: reserve_liquidity_supply.to_account_info(),
+            reserve_collateral_mint: reserve_collateral_mint.to_account_info(),
+            reserve_liquidity_oracle: reserve_liquidity_oracle.to_account_info(),
+            lending_market: lending_market.to_account_info(),
+            clock: clock.to_account_info(),
+            remaining_accounts,
+        })
+    }
+}
@@ -0,0 +1,238 @@
+use crate::errors::ErrorCode;
+use crate::{
+    instructions::randomness::{require_no_pending_request, validate_vrf_client},
+    utils::{CONFIG_STR, LOTTERY_INFO_STR, MULTISIG_STR, PROPOSAL_STR, QUEUED_CHANGE_STR},
+    AdminAction, AdminProposal, AdminTransferProposed, ChangeCancelled, ChangeExecuted,
+    ChangeQueued, LotteryInfo, LotteryStatus, Multisig, ParamChange, ProgramConfig,
+    QueuedChange, VrfClient,
+};
+use anchor_lang::prelude::*;
+use switchboard_v2::VrfAccountData;
+
+pub fn queue_change(ctx: Context<QueueChange>, change: ParamChange) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let target = if change.targets_lottery() {
+        require!(
+            ctx.accounts.lottery_acct.admin == ctx.accounts.proposer.key(),
+            ErrorCode::Unauthorized,
+        );
+        ctx.accounts.lottery_acct.key()
+    } else {
+        require!(
+            ctx.accounts.config.admin == ctx.accounts.proposer.key(),
+            ErrorCode::Unauthorized,
+        );
+        ctx.accounts.config.key()
+    };
+    ctx.accounts.proposal.consume(
+        &ctx.accounts.multisig,
+        &AdminAction::QueueChange {
+            change: change.clone(),
+        },
+    )?;
+
+    let config = &mut ctx.accounts.config;
+    let queued_change = &mut ctx.accounts.queued_change;
+    queued_change.bump = *ctx.bumps.get("queued_change").unwrap();
+    queued_change.id = config.change_count;
+    queued_change.target = target;
+    queued_change.change = change;
+    queued_change.proposer = ctx.accounts.proposer.key();
+    queued_change.queued_at = now;
+    queued_change.eta = now + config.timelock_delay;
+    config.change_count += 1;
+
+    emit!(ChangeQueued {
+        queued_change: queued_change.key(),
+        id: queued_change.id,
+        target,
+        eta: queued_change.eta,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+pub fn execute_change(ctx: Context<ExecuteChange>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let queued_change = &ctx.accounts.queued_change;
+    queued_change.check_ready(now)?;
+
+    if let ParamChange::RandomnessSource(vrf_client) = queued_change.change {
+        // once entries close a known result could be picked by swapping clients
+        require!(
+            ctx.accounts.lottery_acct.status == LotteryStatus::Started.to_code(),
+            ErrorCode::InvalidStatus,
+        );
+        let current_state = ctx
+            .accounts
+            .current_state
+            .as_ref()
+            .ok_or_else(|| error!(ErrorCode::InvalidVrfAccount))?;
+        require_no_pending_request(&ctx.accounts.lottery_acct, current_state)?;
+        match (&ctx.accounts.state, &ctx.accounts.vrf) {
+            (Some(state), Some(vrf)) if state.key() == vrf_client => validate_vrf_client(
+                &ctx.accounts.lottery_acct,
+                &ctx.accounts.config,
+                state,
+                vrf,
+            )?,
+            _ => return Err(error!(ErrorCode::InvalidVrfAccount)),
+        }
+    }
+    if queued_change.target == ctx.accounts.lottery_acct.key() {
+        queued_change
+            .change
+            .apply_to_lottery(&mut ctx.accounts.lottery_acct)?;
+        if let ParamChange::LotteryAdmin(pending_admin) = queued_change.change {
+            emit!(AdminTransferProposed {
+                lottery: ctx.accounts.lottery_acct.key(),
+                admin: ctx.accounts.lottery_acct.admin,
+                pending_admin,
+                timestamp: now,
+            });
+        }
+    } else {
+        queued_change.change.apply_to_config(&mut ctx.accounts.config)?;
+    }
+
+    emit!(ChangeExecuted {
+        queued_change: queued_change.key(),
+        id: queued_change.id,
+        target: queued_change.target,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+pub fn cancel_change(ctx: Context<CancelChange>) -> Result<()> {
+    let queued_change = &ctx.accounts.queued_change;
+    let authority = ctx.accounts.authority.key;
+    let allowed = if queued_change.target == ctx.accounts.lottery_acct.key() {
+        ctx.accounts.lottery_acct.is_guardian(authority)
+    } else {
+        ctx.accounts.config.is_guardian(authority)
+    };
+    require!(allowed, ErrorCode::Unauthorized);
+    ctx.accounts.proposal.consume(
+        &ctx.accounts.multisig,
+        &AdminAction::CancelChange {
+            queued_change: queued_change.key(),
+        },
+    )?;
+
+    emit!(ChangeCancelled {
+        queued_change: queued_change.key(),
+        id: queued_change.id,
+        target: queued_change.target,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct QueueChange<'info> {
+    #[account(
+        init,
+        space = 8 + QueuedChange::MAX_SIZE,
+        payer = proposer,
+        seeds = [QUEUED_CHANGE_STR.as_bytes(), config.change_count.to_le_bytes().as_ref()],
+        bump,
+    )]
+    queued_change: Box<Account<'info, QueuedChange>>,
+    #[account(
+        mut,
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        mut,
+        seeds = [PROPOSAL_STR.as_bytes(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    proposal: Box<Account<'info, AdminProposal>>,
+    #[account(
+        seeds = [MULTISIG_STR.as_bytes(),],
+        bump = multisig.bump,
+    )]
+    multisig: Box<Account<'info, Multisig>>,
+    #[account(mut)]
+    proposer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteChange<'info> {
+    #[account(
+        mut,
+        seeds = [QUEUED_CHANGE_STR.as_bytes(), queued_change.id.to_le_bytes().as_ref()],
+        bump = queued_change.bump,
+        has_one = proposer,
+        close = proposer,
+    )]
+    queued_change: Box<Account<'info, QueuedChange>>,
+    #[account(
+        mut,
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    // RandomnessSource changes only, the client serving the round,
+    // the new VrfClient and its vrf
+    current_state: Option<AccountLoader<'info, VrfClient>>,
+    state: Option<AccountLoader<'info, VrfClient>>,
+    vrf: Option<AccountLoader<'info, VrfAccountData>>,
+    /// CHECK: receives the queued change rent back
+    #[account(mut)]
+    proposer: AccountInfo<'info>,
+    // anyone can execute a change once it is past its eta
+    executor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelChange<'info> {
+    #[account(
+        mut,
+        seeds = [QUEUED_CHANGE_STR.as_bytes(), queued_change.id.to_le_bytes().as_ref()],
+        bump = queued_change.bump,
+        has_one = proposer,
+        close = proposer,
+    )]
+    queued_change: Box<Account<'info, QueuedChange>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        mut,
+        seeds = [PROPOSAL_STR.as_bytes(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    proposal: Box<Account<'info, AdminProposal>>,
+    #[account(
+        seeds = [MULTISIG_STR.as_bytes(),],
+        bump = multisig.bump,
+    )]
+    multisig: Box<Account<'info, Multisig>>,
+    /// CHECK: receives the queued change rent back
+    #[account(mut)]
+    proposer: AccountInfo<'info>,
+    authority: Signer<'info>,
+}
@@ -0,0 +1,295 @@
+use crate::errors::ErrorCode;
+use crate::instructions::strategy::*;
+use crate::strategies::StrategyKind;
+use crate::{
+    pause_flags,
+    utils::{CONFIG_STR, LOTTERY_INFO_STR, VAULT_SIGNER_STR, WITHDRAWAL_QUEUE_STR},
+    FundsRedeemed, LotteryInfo, LotteryStatus, ParticipantLeft, ProgramConfig,
+    QueuedWithdrawalPaid, WithdrawalQueue, WithdrawalQueued,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+/// leaves an invest-on-entry lottery whose reserve can't pay the principal back right
+/// now, the participant drops out of the draw, takes what the liquidity vault holds and
+/// waits in the queue for the rest. the vault goes to the queue first while anyone waits
+pub fn queue_withdrawal(ctx: Context<QueueWithdrawal>) -> Result<()> {
+    let user = ctx.accounts.user.key();
+    let principal = ctx.accounts.lottery_acct.principal_of(user)?;
+    let paid = immediate_payment(
+        &ctx.accounts.lottery_acct,
+        principal,
+        ctx.accounts.source_liquidity_vault.amount,
+    );
+    LotteryInfo::remove_participant(&mut ctx.accounts.lottery_acct, user)?;
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    if paid > 0 {
+        let pda_seeds = &[
+            VAULT_SIGNER_STR.as_bytes(),
+            &[ctx.accounts.lottery_acct.vault_signer_bump],
+        ];
+        token_interface::transfer_checked(
+            ctx.accounts.transfer_tokens_to_user().with_signer(&[pda_seeds.as_ref()]),
+            paid,
+            ctx.accounts.source_liquidity_mint.decimals,
+        )?;
+        emit!(ParticipantLeft {
+            lottery: ctx.accounts.lottery_acct.key(),
+            participant: user,
+            amount: paid,
+            timestamp,
+        });
+    }
+
+    let amount = principal - paid;
+    if amount > 0 {
+        ctx.accounts.lottery_acct.queued_amount += amount;
+        let withdrawal_queue = &mut ctx.accounts.withdrawal_queue;
+        withdrawal_queue.bump = *ctx.bumps.get("withdrawal_queue").unwrap();
+        let id = withdrawal_queue.push(user, amount, timestamp)?;
+
+        emit!(WithdrawalQueued {
+            lottery: ctx.accounts.lottery_acct.key(),
+            user,
+            id,
+            amount,
+            timestamp,
+        });
+    }
+    Ok(())
+}
+
+/// part of `principal` the liquidity vault pays right away, nothing while others are
+/// queued since the vault is theirs first
+fn immediate_payment(lottery_acct: &LotteryInfo, principal: u64, liquidity: u64) -> u64 {
+    if lottery_acct.queued_amount > 0 {
+        return 0;
+    }
+    principal.min(liquidity)
+}
+
+/// permissionless crank paying the head of the queue, redeeming from the primary
+/// reserve whatever it can pay out when the liquidity vault falls short.
+/// the head may be paid in part and stays first until it is paid in full
+pub fn process_withdrawal_queue<'a, 'b, 'c, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, ProcessWithdrawalQueue<'info>>,
+) -> Result<()> {
+    let head = ctx.accounts.withdrawal_queue.head()?;
+    let pda_seeds = &[
+        VAULT_SIGNER_STR.as_bytes(),
+        &[ctx.accounts.lottery_acct.vault_signer_bump],
+    ];
+
+    let liquidity = ctx.accounts.source_liquidity_vault.amount;
+    let shortfall = head.amount.saturating_sub(liquidity);
+    let mut slot = ctx.accounts.lottery_acct.allocation(0);
+    if shortfall > 0
+        && slot.collateral_received > 0
+        && !ctx.accounts.lottery_acct.collateral_staked
+    {
+        require!(
+            ctx.accounts.config.is_active(&ctx.accounts.lottery_acct, pause_flags::LENDING_WITHDRAW),
+            ErrorCode::Paused,
+        );
+        let strategy = StrategyKind::from(slot.strategy)?;
+        let accounts = ctx.accounts.lending.strategy_accounts(
+            &ctx.accounts.source_liquidity_mint,
+            &ctx.accounts.source_liquidity_vault,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+        strategy.validate(&accounts)?;
+        strategy.refresh(&accounts)?;
+
+        // only what the reserve hasn't lent out can be redeemed
+        let reserve_info = strategy.reserve_info(accounts.lending_program.key, &accounts.reserve)?;
+        let collateral_amount = reserve_info
+            .collateral_for_liquidity(shortfall.min(reserve_info.available_liquidity))
+            .min(slot.collateral_received);
+        if collateral_amount > 0 {
+            strategy.redeem(&accounts, collateral_amount, &[pda_seeds.as_ref()])?;
+            ctx.accounts.source_liquidity_vault.reload()?;
+            let liquidity_amount = ctx.accounts.source_liquidity_vault.amount - liquidity;
+            slot.invested_amount -= liquidity_amount.min(slot.invested_amount);
+            slot.collateral_received -= collateral_amount;
+            let lottery_acct = &mut ctx.accounts.lottery_acct;
+            lottery_acct.set_allocation(0, slot);
+
+            emit!(FundsRedeemed {
+                lottery: lottery_acct.key(),
+                collateral_amount,
+                liquidity_amount,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+    }
+
+    let amount = head.amount.min(ctx.accounts.source_liquidity_vault.amount);
+    require!(amount > 0, ErrorCode::NothingToClaim);
+    token_interface::transfer_checked(
+        ctx.accounts.transfer_tokens_to_user().with_signer(&[pda_seeds.as_ref()]),
+        amount,
+        ctx.accounts.source_liquidity_mint.decimals,
+    )?;
+    ctx.accounts.withdrawal_queue.pay_head(amount);
+    ctx.accounts.lottery_acct.queued_amount -= amount;
+
+    emit!(QueuedWithdrawalPaid {
+        lottery: ctx.accounts.lottery_acct.key(),
+        user: head.user,
+        id: head.id,
+        amount,
+        remaining: head.amount - amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct QueueWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        constraint = lottery_acct.status == LotteryStatus::Started.to_code() @ ErrorCode::InvalidStatus,
+        constraint = lottery_acct.invest_on_entry @ ErrorCode::NotInvestOnEntry,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+        constraint = config.is_active(&lottery_acct, pause_flags::PAYOUT) @ ErrorCode::Paused,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + WithdrawalQueue::MAX_SIZE,
+        seeds = [WITHDRAWAL_QUEUE_STR.as_bytes(),],
+        bump,
+    )]
+    withdrawal_queue: Box<Account<'info, WithdrawalQueue>>,
+    #[account(
+        constraint = source_liquidity_mint.key() == lottery_acct.liquidity_mint @ ErrorCode::InvalidMint,
+    )]
+    source_liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint=source_liquidity_mint,
+        associated_token::authority=authority,
+        associated_token::token_program=token_program,
+    )]
+    source_liquidity_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint=source_liquidity_mint,
+        token::authority=user,
+    )]
+    user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump = lottery_acct.vault_signer_bump,
+        )]
+    authority: AccountInfo<'info>,
+    #[account(mut)]
+    user: Signer<'info>,
+    token_program: Interface<'info, TokenInterface>,
+    system_program: Program<'info, System>,
+}
+
+impl<'info> QueueWithdrawal<'info> {
+    pub fn transfer_tokens_to_user(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let transfer_acct = TransferChecked {
+            to: self.user_token_account.to_account_info(),
+            from: self.source_liquidity_vault.to_account_info(),
+            mint: self.source_liquidity_mint.to_account_info(),
+            authority: self.authority.to_account_info(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), transfer_acct)
+    }
+}
+
+#[derive(Accounts)]
+pub struct ProcessWithdrawalQueue<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_INFO_STR.as_bytes(),],
+        bump,
+        constraint = lottery_acct.status != LotteryStatus::Emergency.to_code() @ ErrorCode::InvalidStatus,
+    )]
+    lottery_acct: Box<Account<'info, LotteryInfo>>,
+    #[account(
+        seeds = [CONFIG_STR.as_bytes(),],
+        bump = config.bump,
+        constraint = config.is_active(&lottery_acct, pause_flags::PAYOUT) @ ErrorCode::Paused,
+    )]
+    config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        mut,
+        seeds = [WITHDRAWAL_QUEUE_STR.as_bytes(),],
+        bump = withdrawal_queue.bump,
+    )]
+    withdrawal_queue: Box<Account<'info, WithdrawalQueue>>,
+    #[account(
+        constraint = source_liquidity_mint.key() == lottery_acct.liquidity_mint @ ErrorCode::InvalidMint,
+    )]
+    source_liquidity_mint: InterfaceAccount<'info, Mint>,
+    // has to belong to whoever is first in the queue
+    #[account(
+        mut,
+        token::mint=source_liquidity_mint,
+        constraint = withdrawal_queue.entries.first().map(|entry| entry.user)
+            == Some(user_token_account.owner) @ ErrorCode::Unauthorized,
+    )]
+    user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint=source_liquidity_mint,
+        associated_token::authority=authority,
+        associated_token::token_program=token_program,
+    )]
+    source_liquidity_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: This is the vault signer Acct
+    #[account(
+            seeds = [VAULT_SIGNER_STR.as_bytes()],
+            bump = lottery_acct.vault_signer_bump,
+        )]
+    authority: AccountInfo<'info>,
+    // lending accounts, required when the liquidity vault can't pay the head on its own
+    #[account(
+        constraint = lending.belong_to(&lottery_acct.lending, authority.key) @ ErrorCode::InvalidStrategyAccounts,
+    )]
+    lending: LendingAccounts<'info>,
+    token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ProcessWithdrawalQueue<'info> {
+    pub fn transfer_tokens_to_user(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let transfer_acct = TransferChecked {
+            to: self.user_token_account.to_account_info(),
+            from: self.source_liquidity_vault.to_account_info(),
+            mint: self.source_liquidity_mint.to_account_info(),
+            authority: self.authority.to_account_info(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), transfer_acct)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::lottery_info::tests::lottery;
+
+    #[test]
+    fn pays_what_the_vault_holds_when_nobody_waits() {
+        let mut lottery = lottery(LotteryStatus::Started, &[100, 100]);
+        assert_eq!(immediate_payment(&lottery, 100, 250), 100);
+        assert_eq!(immediate_payment(&lottery, 100, 30), 30);
+        assert_eq!(immediate_payment(&lottery, 100, 0), 0);
+
+        lottery.queued_amount = 40;
+        assert_eq!(immediate_payment(&lottery, 100, 250), 0);
+    }
+}
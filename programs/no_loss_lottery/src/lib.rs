@@ -1,6 +1,8 @@
 pub mod errors;
 pub mod instructions;
 pub mod state;
+pub mod strategies;
+pub mod utils;
 
 pub use instructions::*;
 pub use state::*;
@@ -11,10 +13,6 @@ use anchor_lang::prelude::*;
 
 declare_id!("GFVxvAzesa7PdEhndi4YZLQGKT7Hdgc6RLyZSCFNaMP6");
 
-const MAX_RESULT: u64 = u64::MAX;
-
-const STATE_SEED: &[u8] = b"STATE";
-
 #[program]
 pub mod no_loss_lottery {
 
@@ -29,18 +27,48 @@ pub mod no_loss_lottery {
     pub fn enter_lottery(ctx: Context<EnterLottery>) -> Result<()> {
         instructions::lottery::enter_lottery(ctx)
     }
+    pub fn enter_lottery_native(ctx: Context<EnterLotteryNative>) -> Result<()> {
+        instructions::lottery::enter_lottery_native(ctx)
+    }
+
+    pub fn enter_lottery_invested<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, EnterLotteryInvested<'info>>,
+    ) -> Result<()> {
+        instructions::lottery::enter_lottery_invested(ctx)
+    }
+    pub fn leave_lottery<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, LeaveLottery<'info>>,
+    ) -> Result<()> {
+        instructions::lottery::leave_lottery(ctx)
+    }
+
+    pub fn lock_lottery(ctx: Context<LockLottery>) -> Result<()> {
+        instructions::lottery::lock_lottery(ctx)
+    }
 
+    pub fn rebalance<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, RebalanceLiquidityBuffer<'info>>,
+        allocation: u8,
+    ) -> Result<()> {
+        instructions::lottery::rebalance(ctx, allocation)
+    }
+    pub fn harvest<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, HarvestYield<'info>>,
+        allocation: u8,
+    ) -> Result<()> {
+        instructions::lottery::harvest(ctx, allocation)
+    }
     pub fn withdraw<'a, 'b, 'c, 'info>(
         ctx: Context<'a, 'b, 'c, 'info, WithdrawTokensFromLendingPool<'info>>,
-        vault_signer_bump: u8,
+        allocation: u8,
     ) -> Result<()> {
-        instructions::lottery::withdraw(ctx, vault_signer_bump)
+        instructions::lottery::withdraw(ctx, allocation)
     }
     pub fn deposit<'a, 'b, 'c, 'info>(
         ctx: Context<'a, 'b, 'c, 'info, DepositTokensToLendingPool<'info>>,
-        vault_signer_bump: u8,
+        allocation: u8,
     ) -> Result<()> {
-        instructions::lottery::deposit(ctx, vault_signer_bump)
+        instructions::lottery::deposit(ctx, allocation)
     }
 
     pub fn choose_winner(ctx: Context<ChooseWinner>) -> Result<()> {
@@ -49,6 +77,22 @@ pub mod no_loss_lottery {
     pub fn withdraw_user_tokens(ctx: Context<WithdrawUserTokens>) -> Result<()> {
         instructions::lottery::withdraw_user_tokens(ctx)
     }
+    pub fn withdraw_user_tokens_native(ctx: Context<WithdrawUserTokensNative>) -> Result<()> {
+        instructions::lottery::withdraw_user_tokens_native(ctx)
+    }
+    #[access_control(ctx.accounts.validate(&ctx, &params))]
+    pub fn init_state(ctx: Context<InitState>, params: InitStateParams) -> Result<()> {
+        instructions::randomness::init_state(ctx, params)
+    }
+
+    pub fn register_vrf_client(ctx: Context<RegisterVrfClient>) -> Result<()> {
+        instructions::randomness::register_vrf_client(ctx)
+    }
+
+    pub fn select_vrf_client(ctx: Context<SelectVrfClient>, vrf_client: Pubkey) -> Result<()> {
+        instructions::randomness::select_vrf_client(ctx, vrf_client)
+    }
+
     #[access_control(ctx.accounts.validate(&ctx))]
     pub fn update_result(ctx: Context<UpdateResult>) -> Result<()> {
         instructions::randomness::update_result(ctx)
@@ -59,7 +103,145 @@ pub mod no_loss_lottery {
         instructions::randomness::request_result(ctx, params)
     }
 
-    pub fn close_accounts(ctx: Context<CloseAccounts>) -> Result<()> {
+    pub fn project_prize<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, ProjectPrize<'info>>,
+        draw_time: i64,
+    ) -> Result<PrizeProjection> {
+        instructions::projection::project_prize(ctx, draw_time)
+    }
+
+    pub fn fund_prize(ctx: Context<FundPrize>, amount: u64) -> Result<()> {
+        instructions::lottery::fund_prize(ctx, amount)
+    }
+
+    pub fn queue_withdrawal(ctx: Context<QueueWithdrawal>) -> Result<()> {
+        instructions::withdrawal_queue::queue_withdrawal(ctx)
+    }
+
+    pub fn process_withdrawal_queue<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, ProcessWithdrawalQueue<'info>>,
+    ) -> Result<()> {
+        instructions::withdrawal_queue::process_withdrawal_queue(ctx)
+    }
+
+    pub fn declare_emergency(ctx: Context<DeclareEmergency>) -> Result<()> {
+        instructions::emergency::declare_emergency(ctx)
+    }
+
+    pub fn claim_emergency_share<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, ClaimEmergencyShare<'info>>,
+    ) -> Result<()> {
+        instructions::emergency::claim_emergency_share(ctx)
+    }
+
+    pub fn cancel_lottery(ctx: Context<CancelLottery>) -> Result<()> {
+        instructions::lottery::cancel_lottery(ctx)
+    }
+
+    pub fn close_accounts<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, CloseAccounts<'info>>,
+    ) -> Result<()> {
         instructions::lottery::close_accounts(ctx)
     }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::admin::accept_admin(ctx)
+    }
+    pub fn set_roles(ctx: Context<UpdateLotteryRoles>, params: SetRolesParams) -> Result<()> {
+        instructions::admin::set_roles(ctx, params)
+    }
+
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        params: InitializeConfigParams,
+    ) -> Result<()> {
+        instructions::config::initialize_config(ctx, params)
+    }
+    pub fn update_config(ctx: Context<UpdateConfig>, params: UpdateConfigParams) -> Result<()> {
+        instructions::config::update_config(ctx, params)
+    }
+    pub fn set_global_pause(ctx: Context<SetGlobalPause>, flags: u8) -> Result<()> {
+        instructions::config::set_global_pause(ctx, flags)
+    }
+    pub fn set_lottery_pause(ctx: Context<SetLotteryPause>, flags: u8) -> Result<()> {
+        instructions::config::set_lottery_pause(ctx, flags)
+    }
+
+    pub fn create_multisig(
+        ctx: Context<CreateMultisig>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        instructions::multisig::create_multisig(ctx, signers, threshold)
+    }
+    pub fn create_proposal(ctx: Context<CreateProposal>, action: AdminAction) -> Result<()> {
+        instructions::multisig::create_proposal(ctx, action)
+    }
+    pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+        instructions::multisig::approve_proposal(ctx)
+    }
+    pub fn set_signers(
+        ctx: Context<SetSigners>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        instructions::multisig::set_signers(ctx, signers, threshold)
+    }
+
+    pub fn queue_change(ctx: Context<QueueChange>, change: ParamChange) -> Result<()> {
+        instructions::timelock::queue_change(ctx, change)
+    }
+    pub fn execute_change(ctx: Context<ExecuteChange>) -> Result<()> {
+        instructions::timelock::execute_change(ctx)
+    }
+    pub fn cancel_change(ctx: Context<CancelChange>) -> Result<()> {
+        instructions::timelock::cancel_change(ctx)
+    }
+
+    pub fn init_port_staking(ctx: Context<InitPortStaking>) -> Result<()> {
+        instructions::staking::init_port_staking(ctx)
+    }
+
+    pub fn stake_collateral(ctx: Context<ManageStakedCollateral>) -> Result<()> {
+        instructions::staking::stake_collateral(ctx)
+    }
+
+    pub fn unstake_collateral(ctx: Context<ManageStakedCollateral>) -> Result<()> {
+        instructions::staking::unstake_collateral(ctx)
+    }
+
+    pub fn claim_rewards(ctx: Context<ClaimStakingRewards>) -> Result<()> {
+        instructions::staking::claim_rewards(ctx)
+    }
+
+    pub fn claim_reward_prize(ctx: Context<ClaimRewardPrize>) -> Result<()> {
+        instructions::staking::claim_reward_prize(ctx)
+    }
+
+    pub fn set_allocation(
+        ctx: Context<SetAllocation>,
+        index: u8,
+        strategy: u8,
+        weight_bps: u16,
+    ) -> Result<()> {
+        instructions::strategy::set_allocation(ctx, index, strategy, weight_bps)
+    }
+
+    pub fn migrate_strategy<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, MigrateStrategy<'info>>,
+        new_strategy: u8,
+        old_remaining_accounts: u8,
+    ) -> Result<()> {
+        instructions::strategy::migrate_strategy(ctx, new_strategy, old_remaining_accounts)
+    }
+
+    pub fn migrate_lottery(
+        ctx: Context<MigrateLottery>,
+        params: MigrateLotteryParams,
+    ) -> Result<()> {
+        instructions::migration::migrate_lottery(ctx, params)
+    }
+    pub fn migrate_vrf_client(ctx: Context<MigrateVrfClient>) -> Result<()> {
+        instructions::migration::migrate_vrf_client(ctx)
+    }
 }
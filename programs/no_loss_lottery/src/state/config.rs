@@ -0,0 +1,230 @@
+use crate::errors::ErrorCode;
+use crate::{utils::MAX_BPS, LotteryInfo};
+use anchor_lang::prelude::*;
+
+/// switches that can be flipped on the program config (global) or on a single lottery
+pub mod pause_flags {
+    pub const ENTRY: u8 = 1 << 0;
+    pub const LENDING_DEPOSIT: u8 = 1 << 1;
+    pub const LENDING_WITHDRAW: u8 = 1 << 2;
+    pub const PAYOUT: u8 = 1 << 3;
+    pub const ALL: u8 = ENTRY | LENDING_DEPOSIT | LENDING_WITHDRAW | PAYOUT;
+}
+
+#[account]
+pub struct ProgramConfig {
+    pub bump: u8,
+    pub admin: Pubkey,
+    pub guardian: Pubkey,
+    pub pause_flags: u8,
+    // owner of the token accounts protocol fees are paid into
+    pub treasury: Pubkey,
+    // cut of every prize sent to the treasury
+    pub fee_bps: u16,
+    pub allowed_lending_programs: Vec<Pubkey>,
+    pub allowed_reserves: Vec<Pubkey>,
+    // switchboard oracle queues VRF accounts may be served by
+    pub allowed_oracle_queues: Vec<Pubkey>,
+    // seconds a queued change has to wait before it can be executed
+    pub timelock_delay: i64,
+    // id of the next queued change
+    pub change_count: u64,
+    // Port staking programs collateral may be staked with
+    pub allowed_staking_programs: Vec<Pubkey>,
+}
+
+impl ProgramConfig {
+    pub const MAX_LENDING_PROGRAMS: usize = 4;
+    pub const MAX_RESERVES: usize = 8;
+    pub const MAX_ORACLE_QUEUES: usize = 4;
+    pub const MAX_STAKING_PROGRAMS: usize = 4;
+
+    pub const MAX_SIZE: usize = 1 //bump
+    + 32 //admin
+    + 32 //guardian
+    + 1 //pause_flags
+    + 32 //treasury
+    + 2 //fee_bps
+    + 4 + (32 * Self::MAX_LENDING_PROGRAMS)
+    + 4 + (32 * Self::MAX_RESERVES)
+    + 4 + (32 * Self::MAX_ORACLE_QUEUES)
+    + 8 //timelock_delay
+    + 8 //change_count
+    + 4 + (32 * Self::MAX_STAKING_PROGRAMS);
+
+    pub fn set_fee_bps(&mut self, fee_bps: u16) -> Result<()> {
+        require!(fee_bps as u64 <= MAX_BPS, ErrorCode::InvalidFeeBps);
+        self.fee_bps = fee_bps;
+        Ok(())
+    }
+
+    pub fn set_allowed_lending_programs(&mut self, programs: Vec<Pubkey>) -> Result<()> {
+        require!(
+            programs.len() <= Self::MAX_LENDING_PROGRAMS,
+            ErrorCode::ConfigListFull,
+        );
+        self.allowed_lending_programs = programs;
+        Ok(())
+    }
+
+    pub fn set_allowed_reserves(&mut self, reserves: Vec<Pubkey>) -> Result<()> {
+        require!(reserves.len() <= Self::MAX_RESERVES, ErrorCode::ConfigListFull);
+        self.allowed_reserves = reserves;
+        Ok(())
+    }
+
+    pub fn set_allowed_oracle_queues(&mut self, queues: Vec<Pubkey>) -> Result<()> {
+        require!(
+            queues.len() <= Self::MAX_ORACLE_QUEUES,
+            ErrorCode::ConfigListFull,
+        );
+        self.allowed_oracle_queues = queues;
+        Ok(())
+    }
+
+    pub fn set_allowed_staking_programs(&mut self, programs: Vec<Pubkey>) -> Result<()> {
+        require!(
+            programs.len() <= Self::MAX_STAKING_PROGRAMS,
+            ErrorCode::ConfigListFull,
+        );
+        self.allowed_staking_programs = programs;
+        Ok(())
+    }
+
+    pub fn set_timelock_delay(&mut self, delay: i64) -> Result<()> {
+        require!(delay >= 0, ErrorCode::InvalidTimelockDelay);
+        self.timelock_delay = delay;
+        Ok(())
+    }
+
+    pub fn is_lending_program_allowed(&self, key: &Pubkey) -> bool {
+        self.allowed_lending_programs.contains(key)
+    }
+
+    pub fn is_reserve_allowed(&self, key: &Pubkey) -> bool {
+        self.allowed_reserves.contains(key)
+    }
+
+    pub fn is_oracle_queue_allowed(&self, key: &Pubkey) -> bool {
+        self.allowed_oracle_queues.contains(key)
+    }
+
+    pub fn is_staking_program_allowed(&self, key: &Pubkey) -> bool {
+        self.allowed_staking_programs.contains(key)
+    }
+
+    /// protocol cut taken out of `prize`
+    pub fn fee_on(&self, prize: u64) -> u64 {
+        (prize as u128 * self.fee_bps as u128 / MAX_BPS as u128) as u64
+    }
+
+    pub fn is_guardian(&self, key: &Pubkey) -> bool {
+        *key == self.guardian || *key == self.admin
+    }
+
+    /// an action is allowed only when neither the program nor the lottery paused it
+    pub fn is_active(&self, lottery: &LotteryInfo, flag: u8) -> bool {
+        (self.pause_flags | lottery.pause_flags) & flag == 0
+    }
+}
+
+#[event]
+pub struct PauseFlagsUpdated {
+    // config or lottery account the flags were set on
+    pub target: Pubkey,
+    pub pause_flags: u8,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::state::lottery_info::tests::lottery;
+    use crate::LotteryStatus;
+
+    pub(crate) fn config() -> ProgramConfig {
+        ProgramConfig {
+            bump: 255,
+            admin: Pubkey::new_unique(),
+            guardian: Pubkey::new_unique(),
+            pause_flags: 0,
+            treasury: Pubkey::new_unique(),
+            fee_bps: 0,
+            allowed_lending_programs: vec![],
+            allowed_reserves: vec![],
+            allowed_oracle_queues: vec![],
+            timelock_delay: 0,
+            change_count: 0,
+            allowed_staking_programs: vec![],
+        }
+    }
+
+    #[test]
+    fn pausing_either_side_stops_the_action() {
+        let mut config = config();
+        let mut lottery = lottery(LotteryStatus::Started, &[]);
+        assert!(config.is_active(&lottery, pause_flags::ENTRY));
+
+        config.pause_flags = pause_flags::ENTRY;
+        assert!(!config.is_active(&lottery, pause_flags::ENTRY));
+        assert!(config.is_active(&lottery, pause_flags::PAYOUT));
+
+        config.pause_flags = 0;
+        lottery.pause_flags = pause_flags::PAYOUT;
+        assert!(!config.is_active(&lottery, pause_flags::PAYOUT));
+        assert!(config.is_active(&lottery, pause_flags::LENDING_DEPOSIT));
+
+        config.pause_flags = pause_flags::ALL;
+        lottery.pause_flags = 0;
+        assert!(!config.is_active(&lottery, pause_flags::LENDING_WITHDRAW));
+    }
+
+    #[test]
+    fn only_the_guardian_and_admin_can_pause_globally() {
+        let config = config();
+        assert!(config.is_guardian(&config.guardian));
+        assert!(config.is_guardian(&config.admin));
+        assert!(!config.is_guardian(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn fee_is_capped_at_the_whole_prize() {
+        let mut config = config();
+        config.set_fee_bps(250).unwrap();
+        assert_eq!(config.fee_on(10_000), 250);
+        assert_eq!(config.fee_on(u64::MAX), (u64::MAX as u128 * 250 / 10_000) as u64);
+
+        assert!(config.set_fee_bps(MAX_BPS as u16 + 1).is_err());
+        assert_eq!(config.fee_bps, 250);
+        config.set_fee_bps(MAX_BPS as u16).unwrap();
+        assert_eq!(config.fee_on(10_000), 10_000);
+    }
+
+    #[test]
+    fn allow_lists_are_bounded() {
+        let mut config = config();
+        let keys = |n: usize| (0..n).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+
+        let reserves = keys(ProgramConfig::MAX_RESERVES);
+        config.set_allowed_reserves(reserves.clone()).unwrap();
+        assert!(config.is_reserve_allowed(&reserves[0]));
+        assert!(!config.is_reserve_allowed(&Pubkey::new_unique()));
+
+        assert!(config
+            .set_allowed_reserves(keys(ProgramConfig::MAX_RESERVES + 1))
+            .is_err());
+        assert!(config
+            .set_allowed_lending_programs(keys(ProgramConfig::MAX_LENDING_PROGRAMS + 1))
+            .is_err());
+        assert!(config
+            .set_allowed_oracle_queues(keys(ProgramConfig::MAX_ORACLE_QUEUES + 1))
+            .is_err());
+        assert!(config
+            .set_allowed_staking_programs(keys(ProgramConfig::MAX_STAKING_PROGRAMS + 1))
+            .is_err());
+        // a rejected list leaves the old one in place
+        assert_eq!(config.allowed_reserves, reserves);
+        assert!(config.set_timelock_delay(-1).is_err());
+    }
+}
@@ -1,65 +1,452 @@
 use crate::errors::ErrorCode;
+use crate::strategies::StrategyKind;
+use crate::utils::MAX_BPS;
 use anchor_lang::prelude::*;
 
 #[account]
 pub struct LotteryInfo {
     pub winner: Option<Pubkey>,
     pub entry_fee: u64,
-    pub participants: Vec<Pubkey>,
+    pub participants: Vec<Participant>,
     pub max_participants: u64,
     pub status: u8,
+    // sum of principal credited to the vault by the remaining participants
+    pub total_principal: u64,
+    // can rotate roles and close the lottery
+    pub admin: Pubkey,
+    // admin set by a queued LotteryAdmin change, becomes admin once it accepts
+    pub pending_admin: Option<Pubkey>,
+    // runs the lottery lifecycle: lending deposit/withdraw and choosing the winner
+    pub operator: Pubkey,
+    pub guardian: Pubkey,
+    // see state::config::pause_flags
+    pub pause_flags: u8,
+    // VrfClient choose_winner reads the result from
+    pub vrf_client: Pubkey,
+    pub version: u8,
+    // paid for the lottery accounts, gets the rent back on close
+    pub payer: Pubkey,
+    // see strategies::StrategyKind, zero (Port) for lotteries created before strategies existed
+    pub strategy: u8,
+    // mint participants enter with, the prize vault has to hold the same mint
+    pub liquidity_mint: Pubkey,
+    // reserve the strategy invests into, unset for lotteries without one
+    pub lending: LendingReserve,
+    // canonical bump of the vault signer PDA
+    pub vault_signer_bump: u8,
+    // liquidity moved into the reserve and the collateral minted for it
+    pub invested_amount: u64,
+    pub collateral_received: u64,
+    // liquidity the collateral was redeemed for
+    pub redeemed_amount: u64,
+    // entries go straight into the reserve instead of waiting for deposit
+    pub invest_on_entry: bool,
+    // share of the principal kept liquid in the source_liquidity_vault
+    pub buffer_bps: u16,
+    // the vault held less than total principal at the draw, claims are paid pro-rata
+    pub impaired: bool,
+    // vault liquidity and total principal when the impaired round settled
+    pub settlement_liquidity: u64,
+    pub settlement_principal: u64,
+    // when liquidity first went into a reserve, zero until then
+    pub invested_at: i64,
+    // the primary reserve's collateral sits in the Port obligation, see StakingInfo
+    pub collateral_staked: bool,
+    // still owed to participants waiting in the withdrawal queue, not part of total_principal
+    pub queued_amount: u64,
+    // reserves next to the primary one above, see Allocation
+    pub allocations: [Allocation; 3],
+    // VrfClients registered for the lottery, vrf_client is the one serving the round.
+    // unused slots are the default pubkey
+    pub vrf_clients: [Pubkey; 4],
+    // when entries closed, the draw only takes a VRF result requested after it
+    pub locked_at: i64,
+    // new fields are carved out of this so existing accounts keep deserializing
+    pub reserved: [u8; 128],
+}
+
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize)]
+pub struct Participant {
+    pub pubkey: Pubkey,
+    // amount actually received by the vault on entry
+    pub principal: u64,
+}
+
+impl Participant {
+    pub const SIZE: usize = 32 + 8;
+}
+
+#[derive(Clone, Copy, Default, AnchorSerialize, AnchorDeserialize)]
+pub struct LendingReserve {
+    pub lending_program: Pubkey,
+    pub reserve: Pubkey,
+    pub lending_market: Pubkey,
+    pub collateral_mint: Pubkey,
+}
+
+impl LendingReserve {
+    pub const SIZE: usize = 32 * 4;
+}
+
+/// a reserve holding a weighted share of the invested principal, slot 0 is the
+/// primary reserve kept in the lottery's own strategy and lending fields
+#[derive(Clone, Copy, Default, AnchorSerialize, AnchorDeserialize)]
+pub struct Allocation {
+    pub strategy: u8,
+    pub lending: LendingReserve,
+    // share of the principal the buffer doesn't keep, slot 0 gets what the others leave
+    pub weight_bps: u16,
+    pub invested_amount: u64,
+    pub collateral_received: u64,
+}
+
+impl Allocation {
+    pub const SIZE: usize = 1 + LendingReserve::SIZE + 2 + 8 + 8;
+
+    pub fn is_set(&self) -> bool {
+        self.lending.reserve != Pubkey::default()
+    }
+
+    /// collateral matching `principal` of what was invested in the slot, rounded up so
+    /// redeeming it covers the principal
+    pub fn collateral_for(&self, principal: u64) -> Result<u64> {
+        require!(self.invested_amount > 0, ErrorCode::InvalidStatus);
+        let collateral = (principal as u128 * self.collateral_received as u128
+            + self.invested_amount as u128
+            - 1)
+            / self.invested_amount as u128;
+        Ok((collateral as u64).min(self.collateral_received))
+    }
 }
 
 impl LotteryInfo {
+    pub const LAYOUT_VERSION: u8 = 5;
+    pub const EXTRA_ALLOCATIONS: usize = 3;
+    pub const MAX_VRF_CLIENTS: usize = 4;
+
     pub const MAX_SIZE: usize = 1 + 32 //winner
     + 8
-    + 4 +( 32 * 5)
+    + 4 +( Participant::SIZE * 5)
     + 8
-    + 1;
+    + 1
+    + 8 //total_principal
+    + 32 //admin
+    + 1 + 32 //pending_admin
+    + 32 //operator
+    + 32 //guardian
+    + 1 //pause_flags
+    + 32 //vrf_client
+    + 1 //version
+    + 32 //payer
+    + 1 //strategy
+    + 32 //liquidity_mint
+    + LendingReserve::SIZE
+    + 1 //vault_signer_bump
+    + 8 //invested_amount
+    + 8 //collateral_received
+    + 8 //redeemed_amount
+    + 1 //invest_on_entry
+    + 2 //buffer_bps
+    + 1 //impaired
+    + 8 //settlement_liquidity
+    + 8 //settlement_principal
+    + 8 //invested_at
+    + 1 //collateral_staked
+    + 8 //queued_amount
+    + Allocation::SIZE * 3 //allocations
+    + 32 * 4 //vrf_clients
+    + 8 //locked_at
+    + 128; //reserved
 
-    pub fn init(&mut self, entry_fee: u64, max_participants: u64) -> Result<()> {
+    pub fn init(
+        &mut self,
+        entry_fee: u64,
+        max_participants: u64,
+        admin: Pubkey,
+        operator: Pubkey,
+        guardian: Pubkey,
+        vrf_client: Pubkey,
+        payer: Pubkey,
+        strategy: StrategyKind,
+        liquidity_mint: Pubkey,
+    ) -> Result<()> {
         self.entry_fee = entry_fee;
         self.winner = None;
         self.max_participants = max_participants;
         self.status = LotteryStatus::Started.to_code();
+        self.admin = admin;
+        self.pending_admin = None;
+        self.operator = operator;
+        self.guardian = guardian;
+        self.pause_flags = 0;
+        self.vrf_client = vrf_client;
+        self.vrf_clients = [Pubkey::default(); Self::MAX_VRF_CLIENTS];
+        self.vrf_clients[0] = vrf_client;
+        self.version = Self::LAYOUT_VERSION;
+        self.payer = payer;
+        self.strategy = strategy.to_code();
+        self.liquidity_mint = liquidity_mint;
         Ok(())
     }
 
-    pub fn add_participant(&mut self, new_participant: Pubkey) -> Result<()> {
+    /// the admin can always step in for the operator
+    pub fn is_operator(&self, key: &Pubkey) -> bool {
+        *key == self.operator || *key == self.admin
+    }
+
+    pub fn is_guardian(&self, key: &Pubkey) -> bool {
+        *key == self.guardian || *key == self.admin
+    }
+
+    pub fn add_participant(
+        &mut self,
+        new_participant: Pubkey,
+        principal: u64,
+        now: i64,
+    ) -> Result<()> {
         let index = self
             .participants
             .iter()
-            .position(|participant| *participant == new_participant);
+            .position(|participant| participant.pubkey == new_participant);
 
+        require!(
+            self.status == LotteryStatus::Started.to_code(),
+            ErrorCode::InvalidStatus,
+        );
         require!(index == None, ErrorCode::ParticipantAlreadyAdded);
         require!(
             self.participants.len() < self.max_participants.try_into().unwrap(),
             ErrorCode::ListFull,
         );
-        self.participants.push(new_participant);
+        self.participants.push(Participant {
+            pubkey: new_participant,
+            principal,
+        });
+        self.total_principal += principal;
+        // a full lottery locks itself
+        if self.participants.len() as u64 == self.max_participants {
+            self.lock(now);
+        }
+
+        Ok(())
+    }
+    pub fn principal_of(&self, participant: Pubkey) -> Result<u64> {
+        let participant = self
+            .participants
+            .iter()
+            .find(|p| p.pubkey == participant)
+            .ok_or(ErrorCode::ParticipantNotFound)?;
+        Ok(participant.principal)
+    }
+
+    /// amount owed to `participant` once the winner has been selected,
+    /// the winner also takes whatever the vault made on top of the tracked principal.
+    /// an impaired round pays every principal holder their share of what was left,
+    /// a cancelled lottery only refunds principal
+    pub fn amount_owed(&self, participant: Pubkey, vault_amount: u64) -> Result<u64> {
+        let mut amount_to_pay = self.principal_of(participant)?;
+        match LotteryStatus::from(self.status)? {
+            LotteryStatus::Completed if self.impaired => {
+                amount_to_pay = (amount_to_pay as u128 * self.settlement_liquidity as u128
+                    / self.settlement_principal as u128) as u64;
+            }
+            LotteryStatus::Completed => {
+                if Some(participant) == self.winner {
+                    //includes extra made from lending investment
+                    amount_to_pay += vault_amount.saturating_sub(self.total_principal);
+                }
+            }
+            LotteryStatus::Cancelled => {
+                // refunds wait until the vault holds every principal again
+                require!(!self.is_unwinding(), ErrorCode::AllocationNotEmpty);
+            }
+            _ => return Err(error!(ErrorCode::LotteryStillOn)),
+        }
+        Ok(amount_to_pay)
+    }
+
+    /// closes entries, lotteries investing on entry are already in the reserve
+    pub fn lock(&mut self, now: i64) {
+        self.status = if self.invest_on_entry {
+            LotteryStatus::Invested.to_code()
+        } else {
+            LotteryStatus::Locked.to_code()
+        };
+        self.locked_at = now;
+    }
+
+    /// slot 0 is the primary reserve, the others come from `allocations`,
+    /// unknown slots read as unset
+    pub fn allocation(&self, index: u8) -> Allocation {
+        match index as usize {
+            0 => Allocation {
+                strategy: self.strategy,
+                lending: self.lending,
+                weight_bps: self.primary_weight_bps(),
+                invested_amount: self.invested_amount,
+                collateral_received: self.collateral_received,
+            },
+            i if i <= Self::EXTRA_ALLOCATIONS => self.allocations[i - 1],
+            _ => Allocation::default(),
+        }
+    }
 
+    /// writes `allocation` back to its slot, the primary weight is always derived
+    pub fn set_allocation(&mut self, index: u8, allocation: Allocation) {
+        match index as usize {
+            0 => {
+                self.strategy = allocation.strategy;
+                self.lending = allocation.lending;
+                self.invested_amount = allocation.invested_amount;
+                self.collateral_received = allocation.collateral_received;
+            }
+            i => self.allocations[i - 1] = allocation,
+        }
+    }
+
+    /// another slot already points at `reserve` or shares its collateral vault
+    pub fn is_reserve_used(&self, index: u8, reserve: &Pubkey, collateral_mint: &Pubkey) -> bool {
+        (0..=Self::EXTRA_ALLOCATIONS as u8)
+            .filter(|i| *i != index)
+            .map(|i| self.allocation(i).lending)
+            .any(|lending| {
+                lending.reserve == *reserve || lending.collateral_mint == *collateral_mint
+            })
+    }
+
+    pub fn has_allocation(&self, index: u8) -> bool {
+        index == 0 || self.allocation(index).is_set()
+    }
+
+    pub fn primary_weight_bps(&self) -> u16 {
+        let extra: u64 = self.allocations.iter().map(|a| a.weight_bps as u64).sum();
+        MAX_BPS.saturating_sub(extra) as u16
+    }
+
+    /// principal the slot should hold, its weight of what the buffer doesn't keep
+    pub fn allocation_target(&self, index: u8) -> u64 {
+        let investable = self.total_principal - self.buffer_target(self.total_principal);
+        (investable as u128 * self.allocation(index).weight_bps as u128 / MAX_BPS as u128) as u64
+    }
+
+    /// no reserve holds collateral for the lottery anymore
+    pub fn is_fully_redeemed(&self) -> bool {
+        (0..=Self::EXTRA_ALLOCATIONS as u8).all(|i| self.allocation(i).collateral_received == 0)
+    }
+
+    /// collateral matching `principal` of what went into the primary reserve
+    pub fn collateral_for(&self, principal: u64) -> Result<u64> {
+        self.allocation(0).collateral_for(principal)
+    }
+
+    pub fn set_buffer_bps(&mut self, buffer_bps: u16) -> Result<()> {
+        require!(buffer_bps as u64 <= MAX_BPS, ErrorCode::InvalidBufferBps);
+        self.buffer_bps = buffer_bps;
+        Ok(())
+    }
+
+    /// liquidity the vault should hold for `total_principal`
+    pub fn buffer_target(&self, total_principal: u64) -> u64 {
+        (total_principal as u128 * self.buffer_bps as u128 / MAX_BPS as u128) as u64
+    }
+
+    pub fn is_vrf_client_registered(&self, vrf_client: &Pubkey) -> bool {
+        *vrf_client != Pubkey::default() && self.vrf_clients.contains(vrf_client)
+    }
+
+    pub fn register_vrf_client(&mut self, vrf_client: Pubkey) -> Result<()> {
+        require!(
+            !self.is_vrf_client_registered(&vrf_client),
+            ErrorCode::VrfClientAlreadyRegistered,
+        );
+        let slot = self
+            .vrf_clients
+            .iter_mut()
+            .find(|slot| **slot == Pubkey::default())
+            .ok_or(ErrorCode::TooManyVrfClients)?;
+        *slot = vrf_client;
         Ok(())
     }
+
+    /// the winner hasn't been drawn and the lottery wasn't called off
+    pub fn is_pre_draw(&self) -> bool {
+        matches!(
+            LotteryStatus::from(self.status),
+            Ok(LotteryStatus::Started
+                | LotteryStatus::Locked
+                | LotteryStatus::Invested
+                | LotteryStatus::Redeemed)
+        )
+    }
+
+    /// part of the principal is sitting in the reserve
+    pub fn is_rebalanceable(&self) -> bool {
+        self.status == LotteryStatus::Invested.to_code()
+            || (self.invest_on_entry && self.status == LotteryStatus::Started.to_code())
+    }
+
+    /// funds are back in the liquidity vault (or never left it), a lottery investing on
+    /// entry can be cancelled while Started and is redeemed afterwards, see is_unwinding
+    pub fn is_cancellable(&self) -> bool {
+        matches!(
+            LotteryStatus::from(self.status),
+            Ok(LotteryStatus::Started | LotteryStatus::Locked | LotteryStatus::Redeemed)
+        )
+    }
+
+    /// cancelled while a reserve still holds collateral, the operator redeems it
+    /// before anyone is refunded
+    pub fn is_unwinding(&self) -> bool {
+        self.status == LotteryStatus::Cancelled.to_code() && !self.is_fully_redeemed()
+    }
+
+    /// every claim has been paid out and the lottery can be closed
+    pub fn is_closable(&self) -> bool {
+        self.participants.is_empty()
+            && self.queued_amount == 0
+            && !self.collateral_staked
+            && matches!(
+                LotteryStatus::from(self.status),
+                Ok(LotteryStatus::Settled | LotteryStatus::Cancelled | LotteryStatus::Emergency)
+            )
+    }
+
     pub fn remove_participant(&mut self, current_participant: Pubkey) -> Result<()> {
         let index = self
             .participants
             .iter()
-            .position(|participant| *participant == current_participant);
+            .position(|participant| participant.pubkey == current_participant);
 
         require!(index != None, ErrorCode::ParticipantNotFound);
 
-        self.participants.remove(index.unwrap());
+        let removed = self.participants.remove(index.unwrap());
+        self.total_principal -= removed.principal;
+        if self.participants.is_empty() && self.status == LotteryStatus::Completed.to_code() {
+            self.status = LotteryStatus::Settled.to_code();
+        }
 
         Ok(())
     }
 }
 
-#[derive(PartialEq, Eq,)]
+#[derive(Debug, PartialEq, Eq,)]
 pub enum LotteryStatus {
     //initial stage
     Started,
     //Winner has been selected
     Completed,
+    //Every participant has withdrawn after the draw
+    Settled,
+    //Called off before the draw, participants get their principal back
+    Cancelled,
+    //Entries closed, ready to be invested
+    Locked,
+    //Liquidity sits in the lending reserve
+    Invested,
+    //Collateral redeemed, ready for the draw
+    Redeemed,
+    //Reserve can't be redeemed, participants take their share of the vaults in kind
+    Emergency,
 }
 
 impl LotteryStatus {
@@ -67,6 +454,12 @@ impl LotteryStatus {
         match self {
             LotteryStatus::Started => 0,
             LotteryStatus::Completed => 1,
+            LotteryStatus::Settled => 2,
+            LotteryStatus::Cancelled => 3,
+            LotteryStatus::Locked => 4,
+            LotteryStatus::Invested => 5,
+            LotteryStatus::Redeemed => 6,
+            LotteryStatus::Emergency => 7,
         }
     }
 
@@ -74,7 +467,381 @@ impl LotteryStatus {
         match val {
             0 => Ok(LotteryStatus::Started),
             1 => Ok(LotteryStatus::Completed),
+            2 => Ok(LotteryStatus::Settled),
+            3 => Ok(LotteryStatus::Cancelled),
+            4 => Ok(LotteryStatus::Locked),
+            5 => Ok(LotteryStatus::Invested),
+            6 => Ok(LotteryStatus::Redeemed),
+            7 => Ok(LotteryStatus::Emergency),
             _ => Err(ErrorCode::InvalidStatus.into()),
         }
     }
 }
+
+#[event]
+pub struct AdminTransferProposed {
+    pub lottery: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminTransferred {
+    pub lottery: Pubkey,
+    pub previous_admin: Pubkey,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoleUpdated {
+    pub lottery: Pubkey,
+    pub operator: Pubkey,
+    pub guardian: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LotteryCancelled {
+    pub lottery: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PrizeFunded {
+    pub lottery: Pubkey,
+    pub sponsor: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LotteryLocked {
+    pub lottery: Pubkey,
+    pub participants: u64,
+    pub total_principal: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FundsInvested {
+    pub lottery: Pubkey,
+    pub liquidity_amount: u64,
+    pub collateral_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FundsRedeemed {
+    pub lottery: Pubkey,
+    pub collateral_amount: u64,
+    pub liquidity_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParticipantLeft {
+    pub lottery: Pubkey,
+    pub participant: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct YieldHarvested {
+    pub lottery: Pubkey,
+    pub collateral_amount: u64,
+    pub yield_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoundImpaired {
+    pub lottery: Pubkey,
+    pub total_principal: u64,
+    pub available: u64,
+    // haircut shared pro-rata by every principal holder
+    pub shortfall: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StrategyMigrated {
+    pub lottery: Pubkey,
+    pub old_reserve: Pubkey,
+    pub new_reserve: Pubkey,
+    pub strategy: u8,
+    pub liquidity_amount: u64,
+    pub collateral_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AllocationUpdated {
+    pub lottery: Pubkey,
+    pub index: u8,
+    pub reserve: Pubkey,
+    pub weight_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyDeclared {
+    pub lottery: Pubkey,
+    pub guardian: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyShareClaimed {
+    pub lottery: Pubkey,
+    pub participant: Pubkey,
+    pub liquidity_amount: u64,
+    pub prize_amount: u64,
+    pub reward_amount: u64,
+    // per allocation with a reserve, in slot order
+    pub collateral_amounts: Vec<u64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VrfClientRegistered {
+    pub lottery: Pubkey,
+    pub vrf_client: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VrfClientSelected {
+    pub lottery: Pubkey,
+    pub vrf_client: Pubkey,
+    pub timestamp: i64,
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// a lottery holding `principals`, one participant each
+    pub(crate) fn lottery(status: LotteryStatus, principals: &[u64]) -> LotteryInfo {
+        let participants: Vec<Participant> = principals
+            .iter()
+            .map(|principal| Participant {
+                pubkey: Pubkey::new_unique(),
+                principal: *principal,
+            })
+            .collect();
+        LotteryInfo {
+            winner: None,
+            entry_fee: 100,
+            total_principal: principals.iter().sum(),
+            participants,
+            max_participants: 5,
+            status: status.to_code(),
+            admin: Pubkey::new_unique(),
+            pending_admin: None,
+            operator: Pubkey::new_unique(),
+            guardian: Pubkey::new_unique(),
+            pause_flags: 0,
+            vrf_client: Pubkey::new_unique(),
+            version: LotteryInfo::LAYOUT_VERSION,
+            payer: Pubkey::new_unique(),
+            strategy: StrategyKind::Port.to_code(),
+            liquidity_mint: Pubkey::new_unique(),
+            lending: LendingReserve::default(),
+            vault_signer_bump: 255,
+            invested_amount: 0,
+            collateral_received: 0,
+            redeemed_amount: 0,
+            invest_on_entry: false,
+            buffer_bps: 0,
+            impaired: false,
+            settlement_liquidity: 0,
+            settlement_principal: 0,
+            invested_at: 0,
+            collateral_staked: false,
+            queued_amount: 0,
+            allocations: [Allocation::default(); LotteryInfo::EXTRA_ALLOCATIONS],
+            vrf_clients: [Pubkey::default(); LotteryInfo::MAX_VRF_CLIENTS],
+            locked_at: 0,
+            reserved: [0; 128],
+        }
+    }
+
+    #[test]
+    fn admin_stands_in_for_operator_and_guardian() {
+        let lottery = lottery(LotteryStatus::Started, &[]);
+        assert!(lottery.is_operator(&lottery.operator));
+        assert!(lottery.is_operator(&lottery.admin));
+        assert!(!lottery.is_operator(&lottery.guardian));
+        assert!(lottery.is_guardian(&lottery.guardian));
+        assert!(lottery.is_guardian(&lottery.admin));
+        assert!(!lottery.is_guardian(&lottery.operator));
+        assert!(!lottery.is_operator(&Pubkey::new_unique()));
+        assert!(!lottery.is_guardian(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn closable_once_everyone_is_paid() {
+        assert!(lottery(LotteryStatus::Settled, &[]).is_closable());
+        assert!(lottery(LotteryStatus::Cancelled, &[]).is_closable());
+        assert!(!lottery(LotteryStatus::Completed, &[]).is_closable());
+        assert!(!lottery(LotteryStatus::Cancelled, &[100]).is_closable());
+
+        let mut queued = lottery(LotteryStatus::Settled, &[]);
+        queued.queued_amount = 100;
+        assert!(!queued.is_closable());
+    }
+
+    #[test]
+    fn filling_the_list_locks_at_entry_time() {
+        let mut lottery = lottery(LotteryStatus::Started, &[100; 4]);
+        lottery
+            .add_participant(Pubkey::new_unique(), 100, 1_000)
+            .unwrap();
+        assert_eq!(lottery.status, LotteryStatus::Locked.to_code());
+        assert_eq!(lottery.locked_at, 1_000);
+        assert!(lottery
+            .add_participant(Pubkey::new_unique(), 100, 1_001)
+            .is_err());
+    }
+
+    #[test]
+    fn cancelled_invest_on_entry_refunds_after_redeeming() {
+        let mut lottery = lottery(LotteryStatus::Started, &[100, 100]);
+        lottery.invest_on_entry = true;
+        lottery.invested_amount = 200;
+        lottery.collateral_received = 180;
+        assert!(lottery.is_cancellable());
+
+        lottery.status = LotteryStatus::Cancelled.to_code();
+        let participant = lottery.participants[0].pubkey;
+        assert!(lottery.is_unwinding());
+        assert!(lottery.amount_owed(participant, 200).is_err());
+
+        lottery.collateral_received = 0;
+        assert!(!lottery.is_unwinding());
+        assert_eq!(lottery.amount_owed(participant, 200).unwrap(), 100);
+    }
+
+    #[test]
+    fn not_cancellable_once_invested_or_drawn() {
+        assert!(lottery(LotteryStatus::Locked, &[100]).is_cancellable());
+        assert!(lottery(LotteryStatus::Redeemed, &[100]).is_cancellable());
+        assert!(!lottery(LotteryStatus::Invested, &[100]).is_cancellable());
+        assert!(!lottery(LotteryStatus::Completed, &[100]).is_cancellable());
+    }
+
+    fn reserve() -> LendingReserve {
+        LendingReserve {
+            lending_program: Pubkey::new_unique(),
+            reserve: Pubkey::new_unique(),
+            lending_market: Pubkey::new_unique(),
+            collateral_mint: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn allocations_split_what_the_buffer_leaves() {
+        let mut lottery = lottery(LotteryStatus::Invested, &[500, 500]);
+        lottery.buffer_bps = 1_000;
+        lottery.allocations[0].lending = reserve();
+        lottery.allocations[0].weight_bps = 2_500;
+        lottery.allocations[1].lending = reserve();
+        lottery.allocations[1].weight_bps = 1_500;
+
+        assert_eq!(lottery.primary_weight_bps(), 6_000);
+        assert_eq!(lottery.buffer_target(lottery.total_principal), 100);
+        assert_eq!(lottery.allocation_target(0), 540);
+        assert_eq!(lottery.allocation_target(1), 225);
+        assert_eq!(lottery.allocation_target(2), 135);
+        assert_eq!(lottery.allocation_target(3), 0);
+        assert!(lottery.has_allocation(2) && !lottery.has_allocation(3));
+    }
+
+    #[test]
+    fn reserves_are_not_shared_between_slots() {
+        let mut lottery = lottery(LotteryStatus::Invested, &[100]);
+        lottery.lending = reserve();
+        lottery.allocations[0].lending = reserve();
+        let primary = lottery.lending;
+        let extra = lottery.allocations[0].lending;
+        let fresh = reserve();
+
+        assert!(lottery.is_reserve_used(2, &primary.reserve, &fresh.collateral_mint));
+        assert!(lottery.is_reserve_used(2, &fresh.reserve, &extra.collateral_mint));
+        assert!(!lottery.is_reserve_used(2, &fresh.reserve, &fresh.collateral_mint));
+        // a slot can be pointed at its own reserve again to change the weight
+        assert!(!lottery.is_reserve_used(1, &extra.reserve, &extra.collateral_mint));
+    }
+
+    #[test]
+    fn primary_cant_migrate_onto_an_extra_allocation() {
+        let mut lottery = lottery(LotteryStatus::Invested, &[100]);
+        lottery.lending = reserve();
+        lottery.allocations[1].lending = reserve();
+        let extra = lottery.allocations[1].lending;
+        let fresh = reserve();
+
+        assert!(lottery.is_reserve_used(0, &extra.reserve, &fresh.collateral_mint));
+        assert!(lottery.is_reserve_used(0, &fresh.reserve, &extra.collateral_mint));
+        assert!(!lottery.is_reserve_used(0, &fresh.reserve, &fresh.collateral_mint));
+    }
+
+    #[test]
+    fn not_closable_while_collateral_is_staked() {
+        let mut staked = lottery(LotteryStatus::Emergency, &[]);
+        staked.collateral_staked = true;
+        assert!(!staked.is_closable());
+    }
+
+    #[test]
+    fn winner_takes_the_yield_when_the_reserve_paid_back() {
+        let mut lottery = lottery(LotteryStatus::Completed, &[100, 200]);
+        let (winner, other) = (lottery.participants[0].pubkey, lottery.participants[1].pubkey);
+        lottery.winner = Some(winner);
+        assert_eq!(lottery.amount_owed(winner, 330).unwrap(), 130);
+        assert_eq!(lottery.amount_owed(other, 330).unwrap(), 200);
+        // nothing made, nothing extra
+        assert_eq!(lottery.amount_owed(winner, 250).unwrap(), 100);
+        assert!(lottery.amount_owed(Pubkey::new_unique(), 330).is_err());
+    }
+
+    #[test]
+    fn impaired_lotteries_pay_everyone_pro_rata() {
+        let mut lottery = lottery(LotteryStatus::Completed, &[100, 300]);
+        let (winner, other) = (lottery.participants[0].pubkey, lottery.participants[1].pubkey);
+        lottery.winner = Some(winner);
+        lottery.impaired = true;
+        lottery.settlement_principal = 400;
+        lottery.settlement_liquidity = 300;
+        // the winner doesn't get the vault on top
+        assert_eq!(lottery.amount_owed(winner, 1_000).unwrap(), 75);
+        assert_eq!(lottery.amount_owed(other, 1_000).unwrap(), 225);
+    }
+
+    #[test]
+    fn nothing_owed_before_the_draw() {
+        for status in [
+            LotteryStatus::Started,
+            LotteryStatus::Locked,
+            LotteryStatus::Invested,
+            LotteryStatus::Redeemed,
+        ] {
+            let lottery = lottery(status, &[100]);
+            let participant = lottery.participants[0].pubkey;
+            assert!(lottery.amount_owed(participant, 100).is_err());
+        }
+    }
+}
+
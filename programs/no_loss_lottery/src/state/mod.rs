@@ -1,5 +1,20 @@
+pub mod config;
+pub use config::*;
+
 pub mod lottery_info;
 pub use lottery_info::*;
 
+pub mod multisig;
+pub use multisig::*;
+
+pub mod staking;
+pub use staking::*;
+
+pub mod timelock;
+pub use timelock::*;
+
 pub mod vrf_client;
-pub use vrf_client::*;
\ No newline at end of file
+pub use vrf_client::*;
+
+pub mod withdrawal_queue;
+pub use withdrawal_queue::*;
\ No newline at end of file
@@ -0,0 +1,235 @@
+use crate::errors::ErrorCode;
+use crate::ParamChange;
+use anchor_lang::prelude::*;
+
+/// admin instructions that need M of N approvals before they can run
+#[derive(Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum AdminAction {
+    CloseAccounts,
+    CancelLottery,
+    CancelChange { queued_change: Pubkey },
+    QueueChange { change: ParamChange },
+    SetSigners { signers: Vec<Pubkey>, threshold: u8 },
+    MigrateStrategy { strategy: u8, lending_program: Pubkey, reserve: Pubkey },
+    SetRoles { operator: Option<Pubkey>, guardian: Option<Pubkey> },
+    UpdateConfig { guardian: Option<Pubkey> },
+    SetAllocation { index: u8, strategy: u8, reserve: Pubkey, weight_bps: u16 },
+    SelectVrfClient { vrf_client: Pubkey },
+}
+
+impl AdminAction {
+    // tag plus the largest variant
+    pub const MAX_SIZE: usize = 1 + max(
+        ParamChange::MAX_SIZE, //QueueChange
+        max(
+            4 + (32 * Multisig::MAX_SIGNERS) + 1, //SetSigners
+            1 + 32 + 32, //MigrateStrategy
+        ),
+    );
+}
+
+const fn max(a: usize, b: usize) -> usize {
+    if a > b {
+        a
+    } else {
+        b
+    }
+}
+
+#[account]
+pub struct Multisig {
+    pub bump: u8,
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    // bumped whenever the signer set changes so stale proposals can't be executed
+    pub signer_set_seqno: u32,
+    // id of the next proposal
+    pub proposal_count: u64,
+}
+
+impl Multisig {
+    pub const MAX_SIGNERS: usize = 10;
+
+    pub const MAX_SIZE: usize = 1 //bump
+    + 4 + (32 * Self::MAX_SIGNERS)
+    + 1 //threshold
+    + 4 //signer_set_seqno
+    + 8; //proposal_count
+
+    pub fn set_signers(&mut self, signers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        require!(
+            !signers.is_empty() && signers.len() <= Self::MAX_SIGNERS,
+            ErrorCode::InvalidSigners,
+        );
+        require!(
+            threshold > 0 && threshold as usize <= signers.len(),
+            ErrorCode::InvalidThreshold,
+        );
+        self.signers = signers;
+        self.threshold = threshold;
+        self.signer_set_seqno += 1;
+        Ok(())
+    }
+
+    pub fn signer_index(&self, key: &Pubkey) -> Result<usize> {
+        self.signers
+            .iter()
+            .position(|signer| signer == key)
+            .ok_or_else(|| error!(ErrorCode::NotMultisigSigner))
+    }
+}
+
+#[account]
+pub struct AdminProposal {
+    pub bump: u8,
+    pub id: u64,
+    pub action: AdminAction,
+    pub proposer: Pubkey,
+    // one flag per multisig signer, in signer order
+    pub approvals: Vec<bool>,
+    pub signer_set_seqno: u32,
+    pub executed: bool,
+}
+
+impl AdminProposal {
+    pub const MAX_SIZE: usize = 1 //bump
+    + 8 //id
+    + AdminAction::MAX_SIZE
+    + 32 //proposer
+    + 4 + Multisig::MAX_SIGNERS
+    + 4 //signer_set_seqno
+    + 1; //executed
+
+    pub fn approve(&mut self, multisig: &Multisig, signer: &Pubkey) -> Result<()> {
+        require!(!self.executed, ErrorCode::ProposalAlreadyExecuted);
+        require!(
+            self.signer_set_seqno == multisig.signer_set_seqno,
+            ErrorCode::StaleProposal,
+        );
+        let index = multisig.signer_index(signer)?;
+        self.approvals[index] = true;
+        Ok(())
+    }
+
+    /// marks the proposal executed if it carries `action` and reached the threshold
+    pub fn consume(&mut self, multisig: &Multisig, action: &AdminAction) -> Result<()> {
+        require!(!self.executed, ErrorCode::ProposalAlreadyExecuted);
+        require!(
+            self.signer_set_seqno == multisig.signer_set_seqno,
+            ErrorCode::StaleProposal,
+        );
+        require!(self.action == *action, ErrorCode::ProposalActionMismatch);
+        let approvals = self.approvals.iter().filter(|approved| **approved).count();
+        require!(
+            approvals >= multisig.threshold as usize,
+            ErrorCode::NotEnoughApprovals,
+        );
+        self.executed = true;
+        Ok(())
+    }
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub proposal: Pubkey,
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub proposal: Pubkey,
+    pub signer: Pubkey,
+    pub timestamp: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn multisig(signers: usize, threshold: u8) -> Multisig {
+        let mut multisig = Multisig {
+            bump: 255,
+            signers: vec![],
+            threshold: 0,
+            signer_set_seqno: 0,
+            proposal_count: 0,
+        };
+        multisig
+            .set_signers((0..signers).map(|_| Pubkey::new_unique()).collect(), threshold)
+            .unwrap();
+        multisig
+    }
+
+    fn proposal(multisig: &Multisig, action: AdminAction) -> AdminProposal {
+        AdminProposal {
+            bump: 255,
+            id: 0,
+            action,
+            proposer: multisig.signers[0],
+            approvals: vec![false; multisig.signers.len()],
+            signer_set_seqno: multisig.signer_set_seqno,
+            executed: false,
+        }
+    }
+
+    #[test]
+    fn proposals_need_the_threshold() {
+        let multisig = multisig(3, 2);
+        let mut proposal = proposal(&multisig, AdminAction::CancelLottery);
+
+        assert!(proposal.approve(&multisig, &Pubkey::new_unique()).is_err());
+        proposal.approve(&multisig, &multisig.signers[0]).unwrap();
+        // approving twice doesn't count twice
+        proposal.approve(&multisig, &multisig.signers[0]).unwrap();
+        assert!(proposal
+            .consume(&multisig, &AdminAction::CancelLottery)
+            .is_err());
+        assert!(!proposal.executed);
+
+        proposal.approve(&multisig, &multisig.signers[2]).unwrap();
+        assert!(proposal
+            .consume(&multisig, &AdminAction::CloseAccounts)
+            .is_err());
+        proposal
+            .consume(&multisig, &AdminAction::CancelLottery)
+            .unwrap();
+        assert!(proposal.executed);
+
+        // can't be replayed
+        assert!(proposal
+            .consume(&multisig, &AdminAction::CancelLottery)
+            .is_err());
+        assert!(proposal.approve(&multisig, &multisig.signers[1]).is_err());
+    }
+
+    #[test]
+    fn changing_signers_voids_open_proposals() {
+        let mut multisig = multisig(2, 1);
+        let mut proposal = proposal(&multisig, AdminAction::CancelLottery);
+        proposal.approve(&multisig, &multisig.signers[0]).unwrap();
+
+        let signers = multisig.signers.clone();
+        multisig.set_signers(signers, 2).unwrap();
+        assert!(proposal
+            .consume(&multisig, &AdminAction::CancelLottery)
+            .is_err());
+        assert!(proposal.approve(&multisig, &multisig.signers[1]).is_err());
+    }
+
+    #[test]
+    fn signer_sets_are_validated() {
+        let mut multisig = multisig(2, 1);
+        let signers = multisig.signers.clone();
+        assert!(multisig.set_signers(signers.clone(), 0).is_err());
+        assert!(multisig.set_signers(signers, 3).is_err());
+        assert!(multisig.set_signers(vec![], 0).is_err());
+        let too_many = (0..=Multisig::MAX_SIGNERS)
+            .map(|_| Pubkey::new_unique())
+            .collect();
+        assert!(multisig.set_signers(too_many, 1).is_err());
+        assert_eq!(multisig.signer_set_seqno, 1);
+        assert_eq!(multisig.threshold, 1);
+    }
+}
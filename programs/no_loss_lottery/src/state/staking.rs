@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+
+/// the lottery's Port staking position, collateral moved into `obligation`
+/// is staked in `staking_pool` and earns `reward_mint`
+#[account]
+pub struct StakingInfo {
+    pub bump: u8,
+    pub staking_program: Pubkey,
+    pub staking_pool: Pubkey,
+    pub stake_account: Pubkey,
+    // Port obligation owned by the vault signer
+    pub obligation: Pubkey,
+    pub reward_mint: Pubkey,
+    // collateral currently sitting in the obligation
+    pub staked_amount: u64,
+}
+
+impl StakingInfo {
+    pub const MAX_SIZE: usize = 1 //bump
+    + 32 //staking_program
+    + 32 //staking_pool
+    + 32 //stake_account
+    + 32 //obligation
+    + 32 //reward_mint
+    + 8; //staked_amount
+}
+
+#[event]
+pub struct CollateralStaked {
+    pub lottery: Pubkey,
+    pub collateral_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CollateralUnstaked {
+    pub lottery: Pubkey,
+    pub collateral_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardsClaimed {
+    pub lottery: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardPrizePaid {
+    pub lottery: Pubkey,
+    pub winner: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,173 @@
+use crate::errors::ErrorCode;
+use crate::{LotteryInfo, ProgramConfig};
+use anchor_lang::prelude::*;
+
+/// sensitive parameter updates, they only apply once their queued change is past its eta
+#[derive(Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum ParamChange {
+    // program config
+    FeeBps(u16),
+    Treasury(Pubkey),
+    AllowedLendingPrograms(Vec<Pubkey>),
+    AllowedReserves(Vec<Pubkey>),
+    AllowedOracleQueues(Vec<Pubkey>),
+    ConfigAdmin(Pubkey),
+    TimelockDelay(i64),
+    // lottery
+    RandomnessSource(Pubkey),
+    LotteryAdmin(Pubkey),
+    LotteryBufferBps(u16),
+    // program config, after the lottery ones so queued changes keep their tags
+    AllowedStakingPrograms(Vec<Pubkey>),
+}
+
+impl ParamChange {
+    // largest variant is a full allowed_reserves list
+    pub const MAX_SIZE: usize = 1 + 4 + (32 * ProgramConfig::MAX_RESERVES);
+
+    pub fn targets_lottery(&self) -> bool {
+        matches!(
+            self,
+            ParamChange::RandomnessSource(_)
+                | ParamChange::LotteryAdmin(_)
+                | ParamChange::LotteryBufferBps(_)
+        )
+    }
+
+    pub fn apply_to_config(&self, config: &mut ProgramConfig) -> Result<()> {
+        match self.clone() {
+            ParamChange::FeeBps(fee_bps) => config.set_fee_bps(fee_bps)?,
+            ParamChange::Treasury(treasury) => config.treasury = treasury,
+            ParamChange::AllowedLendingPrograms(programs) => {
+                config.set_allowed_lending_programs(programs)?
+            }
+            ParamChange::AllowedReserves(reserves) => config.set_allowed_reserves(reserves)?,
+            ParamChange::AllowedOracleQueues(queues) => config.set_allowed_oracle_queues(queues)?,
+            ParamChange::ConfigAdmin(admin) => config.admin = admin,
+            ParamChange::TimelockDelay(delay) => config.set_timelock_delay(delay)?,
+            ParamChange::AllowedStakingPrograms(programs) => {
+                config.set_allowed_staking_programs(programs)?
+            }
+            _ => return Err(error!(ErrorCode::InvalidChangeTarget)),
+        }
+        Ok(())
+    }
+
+    pub fn apply_to_lottery(&self, lottery: &mut LotteryInfo) -> Result<()> {
+        match self.clone() {
+            ParamChange::RandomnessSource(vrf_client) => {
+                if !lottery.is_vrf_client_registered(&vrf_client) {
+                    lottery.register_vrf_client(vrf_client)?;
+                }
+                lottery.vrf_client = vrf_client;
+            }
+            // the new admin still has to accept_admin
+            ParamChange::LotteryAdmin(admin) => lottery.pending_admin = Some(admin),
+            ParamChange::LotteryBufferBps(buffer_bps) => lottery.set_buffer_bps(buffer_bps)?,
+            _ => return Err(error!(ErrorCode::InvalidChangeTarget)),
+        }
+        Ok(())
+    }
+}
+
+#[account]
+pub struct QueuedChange {
+    pub bump: u8,
+    pub id: u64,
+    // config or lottery account the change applies to
+    pub target: Pubkey,
+    pub change: ParamChange,
+    pub proposer: Pubkey,
+    pub queued_at: i64,
+    // earliest unix timestamp the change can be executed at
+    pub eta: i64,
+}
+
+impl QueuedChange {
+    pub const MAX_SIZE: usize = 1 //bump
+    + 8 //id
+    + 32 //target
+    + ParamChange::MAX_SIZE
+    + 32 //proposer
+    + 8 //queued_at
+    + 8; //eta
+
+    pub fn check_ready(&self, now: i64) -> Result<()> {
+        require!(now >= self.eta, ErrorCode::TimelockNotExpired);
+        Ok(())
+    }
+}
+
+#[event]
+pub struct ChangeQueued {
+    pub queued_change: Pubkey,
+    pub id: u64,
+    pub target: Pubkey,
+    pub eta: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChangeExecuted {
+    pub queued_change: Pubkey,
+    pub id: u64,
+    pub target: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChangeCancelled {
+    pub queued_change: Pubkey,
+    pub id: u64,
+    pub target: Pubkey,
+    pub timestamp: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::config::tests::config;
+    use crate::state::lottery_info::tests::lottery;
+    use crate::LotteryStatus;
+
+    fn queued_change(change: ParamChange, eta: i64) -> QueuedChange {
+        QueuedChange {
+            bump: 255,
+            id: 0,
+            target: Pubkey::new_unique(),
+            change,
+            proposer: Pubkey::new_unique(),
+            queued_at: eta - 100,
+            eta,
+        }
+    }
+
+    #[test]
+    fn changes_wait_for_their_eta() {
+        let queued_change = queued_change(ParamChange::FeeBps(100), 1_000);
+        assert!(queued_change.check_ready(999).is_err());
+        assert!(queued_change.check_ready(1_000).is_ok());
+        assert!(queued_change.check_ready(1_001).is_ok());
+    }
+
+    #[test]
+    fn changes_only_apply_to_their_own_target() {
+        let mut config = config();
+        let mut lottery = lottery(LotteryStatus::Started, &[]);
+
+        let fee = ParamChange::FeeBps(100);
+        assert!(!fee.targets_lottery());
+        assert!(fee.apply_to_lottery(&mut lottery).is_err());
+        fee.apply_to_config(&mut config).unwrap();
+        assert_eq!(config.fee_bps, 100);
+
+        let admin = Pubkey::new_unique();
+        let lottery_admin = ParamChange::LotteryAdmin(admin);
+        assert!(lottery_admin.targets_lottery());
+        assert!(lottery_admin.apply_to_config(&mut config).is_err());
+        lottery_admin.apply_to_lottery(&mut lottery).unwrap();
+        // still has to be accepted
+        assert_eq!(lottery.pending_admin, Some(admin));
+        assert_ne!(lottery.admin, admin);
+    }
+}
@@ -1,7 +1,8 @@
+use crate::errors::ErrorCode;
 use anchor_lang::prelude::*;
 
 #[repr(packed)]
-#[account(zero_copy)]
+#[account(zero_copy(unsafe))]
 pub struct VrfClient {
     pub bump: u8,
     pub max_result: u64,
@@ -10,6 +11,37 @@ pub struct VrfClient {
     pub last_timestamp: i64,
     pub authority: Pubkey,
     pub vrf: Pubkey,
+    pub version: u8,
+    // when randomness was last requested, the request is pending until a result lands
+    pub requested_at: i64,
+    pub reserved: [u8; 56],
+}
+impl VrfClient {
+    pub const LAYOUT_VERSION: u8 = 1;
+
+    pub fn is_request_pending(&self) -> bool {
+        let (requested_at, last_timestamp) = (self.requested_at, self.last_timestamp);
+        requested_at > last_timestamp
+    }
+
+    /// the result has to answer a request made after `locked_at`, anything older
+    /// could have been known while entries were still open
+    pub fn check_drawn_after(&self, locked_at: i64) -> Result<()> {
+        let requested_at = self.requested_at;
+        require!(requested_at > locked_at, ErrorCode::StaleVrfResult);
+        require!(!self.is_request_pending(), ErrorCode::VrfRequestPending);
+        Ok(())
+    }
+
+    /// index into a list of `len` entries, `result` is bounded by max_result and the
+    /// list can be shorter so the raw randomness is reduced instead
+    pub fn draw(&self, len: usize) -> Result<usize> {
+        let result_buffer = self.result_buffer;
+        require!(result_buffer != [0u8; 32], ErrorCode::VrfResultMissing);
+        require!(len > 0, ErrorCode::NoParticipants);
+        let randomness = u128::from_le_bytes(result_buffer[..16].try_into().unwrap());
+        Ok((randomness % len as u128) as usize)
+    }
 }
 impl Default for VrfClient {
     fn default() -> Self {
@@ -37,3 +69,56 @@ pub struct VrfClientResultUpdated {
     pub result_buffer: [u8; 32],
     pub timestamp: i64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(randomness: u128) -> VrfClient {
+        let mut client = VrfClient::default();
+        client.result_buffer[..16].copy_from_slice(&randomness.to_le_bytes());
+        client.result_buffer[31] = 1;
+        client
+    }
+
+    #[test]
+    fn draw_stays_inside_a_shorter_list() {
+        // max_result was 5 but only 3 entered
+        assert_eq!(client(4).draw(3).unwrap(), 1);
+        assert_eq!(client(4).draw(5).unwrap(), 4);
+        assert!(client(u128::MAX).draw(3).unwrap() < 3);
+    }
+
+    #[test]
+    fn request_is_pending_until_a_result_lands() {
+        let mut client = client(4);
+        client.last_timestamp = 100;
+        assert!(!client.is_request_pending());
+        client.requested_at = 150;
+        assert!(client.is_request_pending());
+        client.last_timestamp = 160;
+        assert!(!client.is_request_pending());
+    }
+
+    #[test]
+    fn results_requested_before_the_lock_are_stale() {
+        let mut client = client(4);
+        // fulfilled before the lock, relayed after it
+        client.requested_at = 90;
+        client.last_timestamp = 120;
+        assert!(client.check_drawn_after(100).is_err());
+
+        // requested after the lock but not fulfilled yet
+        client.requested_at = 130;
+        assert!(client.check_drawn_after(100).is_err());
+
+        client.last_timestamp = 140;
+        assert!(client.check_drawn_after(100).is_ok());
+    }
+
+    #[test]
+    fn draw_needs_a_result_and_participants() {
+        assert!(VrfClient::default().draw(3).is_err());
+        assert!(client(4).draw(0).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use crate::errors::ErrorCode;
+use anchor_lang::prelude::*;
+
+/// withdrawals the liquidity vault and the reserve couldn't pay yet,
+/// paid first-in-first-out by process_withdrawal_queue
+#[account]
+pub struct WithdrawalQueue {
+    pub bump: u8,
+    // id of the next queued withdrawal
+    pub next_id: u64,
+    pub entries: Vec<QueuedWithdrawal>,
+}
+
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize)]
+pub struct QueuedWithdrawal {
+    pub id: u64,
+    pub user: Pubkey,
+    // still owed, shrinks as partial payments go out
+    pub amount: u64,
+    pub queued_at: i64,
+}
+
+impl QueuedWithdrawal {
+    pub const SIZE: usize = 8 + 32 + 8 + 8;
+}
+
+impl WithdrawalQueue {
+    pub const MAX_ENTRIES: usize = 32;
+
+    pub const MAX_SIZE: usize = 1 //bump
+    + 8 //next_id
+    + 4 + (QueuedWithdrawal::SIZE * Self::MAX_ENTRIES);
+
+    pub fn push(&mut self, user: Pubkey, amount: u64, queued_at: i64) -> Result<u64> {
+        require!(
+            self.entries.len() < Self::MAX_ENTRIES,
+            ErrorCode::WithdrawalQueueFull,
+        );
+        let id = self.next_id;
+        self.entries.push(QueuedWithdrawal {
+            id,
+            user,
+            amount,
+            queued_at,
+        });
+        self.next_id += 1;
+        Ok(id)
+    }
+
+    pub fn head(&self) -> Result<QueuedWithdrawal> {
+        self.entries
+            .first()
+            .copied()
+            .ok_or_else(|| error!(ErrorCode::WithdrawalQueueEmpty))
+    }
+
+    /// takes `amount` off the head, which leaves the queue once it is fully paid
+    pub fn pay_head(&mut self, amount: u64) {
+        if let Some(head) = self.entries.first_mut() {
+            head.amount -= amount;
+            if head.amount == 0 {
+                self.entries.remove(0);
+            }
+        }
+    }
+
+    pub fn remove_user(&mut self, user: Pubkey) -> Result<QueuedWithdrawal> {
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| entry.user == user)
+            .ok_or(ErrorCode::ParticipantNotFound)?;
+        Ok(self.entries.remove(index))
+    }
+}
+
+#[event]
+pub struct WithdrawalQueued {
+    pub lottery: Pubkey,
+    pub user: Pubkey,
+    pub id: u64,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct QueuedWithdrawalPaid {
+    pub lottery: Pubkey,
+    pub user: Pubkey,
+    pub id: u64,
+    pub amount: u64,
+    // left to pay, zero once the withdrawal has left the queue
+    pub remaining: u64,
+    pub timestamp: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue() -> WithdrawalQueue {
+        WithdrawalQueue {
+            bump: 255,
+            next_id: 0,
+            entries: vec![],
+        }
+    }
+
+    #[test]
+    fn pays_first_in_first_out() {
+        let mut queue = queue();
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(queue.push(first, 100, 1).unwrap(), 0);
+        assert_eq!(queue.push(second, 50, 2).unwrap(), 1);
+
+        // a partial payment keeps the head first
+        queue.pay_head(60);
+        let head = queue.head().unwrap();
+        assert_eq!((head.user, head.amount), (first, 40));
+
+        queue.pay_head(40);
+        let head = queue.head().unwrap();
+        assert_eq!((head.user, head.id, head.amount), (second, 1, 50));
+
+        queue.pay_head(50);
+        assert!(queue.head().is_err());
+        assert_eq!(queue.push(first, 10, 3).unwrap(), 2);
+    }
+
+    #[test]
+    fn rejects_a_full_queue() {
+        let mut queue = queue();
+        for i in 0..WithdrawalQueue::MAX_ENTRIES {
+            queue.push(Pubkey::new_unique(), 1, i as i64).unwrap();
+        }
+        assert!(queue.push(Pubkey::new_unique(), 1, 0).is_err());
+    }
+
+    #[test]
+    fn removes_a_user_anywhere_in_the_queue() {
+        let mut queue = queue();
+        let users = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        for (i, user) in users.iter().enumerate() {
+            queue.push(*user, 10 * (i as u64 + 1), 0).unwrap();
+        }
+        assert_eq!(queue.remove_user(users[1]).unwrap().amount, 20);
+        assert!(queue.remove_user(users[1]).is_err());
+        assert_eq!(queue.head().unwrap().user, users[0]);
+        assert_eq!(queue.entries[1].user, users[2]);
+    }
+}
@@ -0,0 +1,293 @@
+pub mod none;
+pub mod port;
+pub mod port_staking;
+pub mod solend;
+
+pub use none::*;
+pub use port::*;
+pub use solend::*;
+
+use crate::errors::ErrorCode;
+use anchor_lang::prelude::*;
+
+/// accounts every adapter gets handed, adapters only touch the ones their
+/// lending market needs and pull anything extra out of `remaining_accounts`
+pub struct StrategyAccounts<'a, 'info> {
+    pub lending_program: AccountInfo<'info>,
+    pub source_liquidity_mint: AccountInfo<'info>,
+    pub source_liquidity_vault: AccountInfo<'info>,
+    pub destination_collateral_mint: AccountInfo<'info>,
+    pub destination_collateral_vault: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub reserve: AccountInfo<'info>,
+    pub reserve_liquidity_supply: AccountInfo<'info>,
+    pub reserve_collateral_mint: AccountInfo<'info>,
+    pub reserve_liquidity_oracle: AccountInfo<'info>,
+    pub lending_market: AccountInfo<'info>,
+    pub clock: AccountInfo<'info>,
+    pub remaining_accounts: &'a [AccountInfo<'info>],
+}
+
+/// the parts of a lending reserve the lottery's accounts are checked against
+pub struct ReserveInfo {
+    pub lending_market: Pubkey,
+    pub liquidity_mint: Pubkey,
+    pub liquidity_supply: Pubkey,
+    // None when the reserve has no price oracle
+    pub liquidity_oracle: Option<Pubkey>,
+    pub collateral_mint: Pubkey,
+    // available plus borrowed liquidity, backs collateral_supply
+    pub total_liquidity: u64,
+    // not lent out, the most redemptions can pay right now
+    pub available_liquidity: u64,
+    pub collateral_supply: u64,
+}
+
+impl ReserveInfo {
+    /// liquidity `collateral_amount` redeems for at the current exchange rate
+    pub fn collateral_value(&self, collateral_amount: u64) -> u64 {
+        if self.collateral_supply == 0 {
+            return 0;
+        }
+        (collateral_amount as u128 * self.total_liquidity as u128
+            / self.collateral_supply as u128) as u64
+    }
+
+    /// collateral worth at most `liquidity_amount`, rounded down
+    pub fn collateral_for_liquidity(&self, liquidity_amount: u64) -> u64 {
+        if self.total_liquidity == 0 {
+            return 0;
+        }
+        (liquidity_amount as u128 * self.collateral_supply as u128
+            / self.total_liquidity as u128) as u64
+    }
+
+    pub fn check(&self, accounts: &StrategyAccounts) -> Result<()> {
+        require!(
+            *accounts.lending_market.key == self.lending_market
+                && *accounts.source_liquidity_mint.key == self.liquidity_mint
+                && *accounts.reserve_liquidity_supply.key == self.liquidity_supply
+                && *accounts.reserve_collateral_mint.key == self.collateral_mint
+                && *accounts.destination_collateral_mint.key == self.collateral_mint,
+            ErrorCode::InvalidStrategyAccounts,
+        );
+        if let Some(liquidity_oracle) = self.liquidity_oracle {
+            require!(
+                *accounts.reserve_liquidity_oracle.key == liquidity_oracle,
+                ErrorCode::InvalidStrategyAccounts,
+            );
+        }
+        Ok(())
+    }
+}
+
+const WAD: u128 = 1_000_000_000_000_000_000;
+
+pub(crate) fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    let bytes = data
+        .get(offset..offset + 8)
+        .ok_or_else(|| error!(ErrorCode::InvalidStrategyAccounts))?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// whole tokens out of a WAD (1e18) scaled u128
+pub(crate) fn read_wad(data: &[u8], offset: usize) -> Result<u64> {
+    let bytes = data
+        .get(offset..offset + 16)
+        .ok_or_else(|| error!(ErrorCode::InvalidStrategyAccounts))?;
+    Ok((u128::from_le_bytes(bytes.try_into().unwrap()) / WAD) as u64)
+}
+
+pub(crate) fn read_pubkey(data: &[u8], offset: usize) -> Result<Pubkey> {
+    let bytes = data
+        .get(offset..offset + 32)
+        .ok_or_else(|| error!(ErrorCode::InvalidStrategyAccounts))?;
+    Ok(Pubkey::new_from_array(bytes.try_into().unwrap()))
+}
+
+/// a place the vault liquidity can be put to work,
+/// `signer_seeds` are the vault signer seeds the adapter signs with
+pub trait YieldStrategy {
+    /// checks the account set belongs to this strategy
+    fn validate(accounts: &StrategyAccounts) -> Result<()>;
+
+    /// reads the reserve account owned by `lending_program`
+    fn reserve_info(lending_program: &Pubkey, reserve: &AccountInfo) -> Result<ReserveInfo>;
+
+    fn refresh(accounts: &StrategyAccounts) -> Result<()>;
+
+    /// moves `liquidity_amount` from the liquidity vault into the market
+    fn deposit(
+        accounts: &StrategyAccounts,
+        liquidity_amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()>;
+
+    /// burns `collateral_amount` from the collateral vault back into liquidity
+    fn redeem(
+        accounts: &StrategyAccounts,
+        collateral_amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrategyKind {
+    //Port variable rate lending
+    Port,
+    //liquidity stays in the vault
+    None,
+    //Solend token lending
+    Solend,
+}
+
+impl StrategyKind {
+    pub fn to_code(&self) -> u8 {
+        match self {
+            StrategyKind::Port => 0,
+            StrategyKind::None => 1,
+            StrategyKind::Solend => 2,
+        }
+    }
+
+    pub fn from(val: u8) -> std::result::Result<StrategyKind, ErrorCode> {
+        match val {
+            0 => Ok(StrategyKind::Port),
+            1 => Ok(StrategyKind::None),
+            2 => Ok(StrategyKind::Solend),
+            _ => Err(ErrorCode::InvalidStrategy),
+        }
+    }
+
+    /// adapter checks plus every reserve account matching what the reserve itself records
+    pub fn validate(&self, accounts: &StrategyAccounts) -> Result<()> {
+        match self {
+            StrategyKind::Port => PortStrategy::validate(accounts),
+            StrategyKind::None => NoStrategy::validate(accounts),
+            StrategyKind::Solend => SolendStrategy::validate(accounts),
+        }?;
+        self.reserve_info(accounts.lending_program.key, &accounts.reserve)?
+            .check(accounts)
+    }
+
+    pub fn reserve_info(&self, lending_program: &Pubkey, reserve: &AccountInfo) -> Result<ReserveInfo> {
+        require!(
+            reserve.owner == lending_program,
+            ErrorCode::InvalidStrategyAccounts,
+        );
+        match self {
+            StrategyKind::Port => PortStrategy::reserve_info(lending_program, reserve),
+            StrategyKind::None => NoStrategy::reserve_info(lending_program, reserve),
+            StrategyKind::Solend => SolendStrategy::reserve_info(lending_program, reserve),
+        }
+    }
+
+    pub fn refresh(&self, accounts: &StrategyAccounts) -> Result<()> {
+        match self {
+            StrategyKind::Port => PortStrategy::refresh(accounts),
+            StrategyKind::None => NoStrategy::refresh(accounts),
+            StrategyKind::Solend => SolendStrategy::refresh(accounts),
+        }
+    }
+
+    pub fn deposit(
+        &self,
+        accounts: &StrategyAccounts,
+        liquidity_amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        match self {
+            StrategyKind::Port => PortStrategy::deposit(accounts, liquidity_amount, signer_seeds),
+            StrategyKind::None => NoStrategy::deposit(accounts, liquidity_amount, signer_seeds),
+            StrategyKind::Solend => SolendStrategy::deposit(accounts, liquidity_amount, signer_seeds),
+        }
+    }
+
+    pub fn redeem(
+        &self,
+        accounts: &StrategyAccounts,
+        collateral_amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        match self {
+            StrategyKind::Port => PortStrategy::redeem(accounts, collateral_amount, signer_seeds),
+            StrategyKind::None => NoStrategy::redeem(accounts, collateral_amount, signer_seeds),
+            StrategyKind::Solend => SolendStrategy::redeem(accounts, collateral_amount, signer_seeds),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(key: Pubkey) -> AccountInfo<'static> {
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            false,
+            false,
+            Box::leak(Box::new(0)),
+            Box::leak(Box::new([])),
+            Box::leak(Box::new(Pubkey::default())),
+            false,
+            0,
+        )
+    }
+
+    fn reserve_info(liquidity_oracle: Option<Pubkey>) -> ReserveInfo {
+        ReserveInfo {
+            lending_market: Pubkey::new_unique(),
+            liquidity_mint: Pubkey::new_unique(),
+            liquidity_supply: Pubkey::new_unique(),
+            liquidity_oracle,
+            collateral_mint: Pubkey::new_unique(),
+            total_liquidity: 0,
+            available_liquidity: 0,
+            collateral_supply: 0,
+        }
+    }
+
+    fn accounts(reserve: &ReserveInfo, oracle: Pubkey) -> StrategyAccounts<'static, 'static> {
+        StrategyAccounts {
+            lending_program: info(Pubkey::new_unique()),
+            source_liquidity_mint: info(reserve.liquidity_mint),
+            source_liquidity_vault: info(Pubkey::new_unique()),
+            destination_collateral_mint: info(reserve.collateral_mint),
+            destination_collateral_vault: info(Pubkey::new_unique()),
+            authority: info(Pubkey::new_unique()),
+            reserve: info(Pubkey::new_unique()),
+            reserve_liquidity_supply: info(reserve.liquidity_supply),
+            reserve_collateral_mint: info(reserve.collateral_mint),
+            reserve_liquidity_oracle: info(oracle),
+            lending_market: info(reserve.lending_market),
+            clock: info(Pubkey::new_unique()),
+            remaining_accounts: &[],
+        }
+    }
+
+    #[test]
+    fn accounts_must_match_the_reserve_mints() {
+        let oracle = Pubkey::new_unique();
+        let reserve = reserve_info(Some(oracle));
+        assert!(reserve.check(&accounts(&reserve, oracle)).is_ok());
+        assert!(reserve
+            .check(&accounts(&reserve, Pubkey::new_unique()))
+            .is_err());
+
+        let mut wrong_liquidity_mint = accounts(&reserve, oracle);
+        wrong_liquidity_mint.source_liquidity_mint = info(Pubkey::new_unique());
+        assert!(reserve.check(&wrong_liquidity_mint).is_err());
+
+        let mut wrong_collateral_mint = accounts(&reserve, oracle);
+        wrong_collateral_mint.destination_collateral_mint = info(Pubkey::new_unique());
+        assert!(reserve.check(&wrong_collateral_mint).is_err());
+    }
+
+    #[test]
+    fn reserves_without_an_oracle_take_any() {
+        let reserve = reserve_info(None);
+        assert!(reserve
+            .check(&accounts(&reserve, Pubkey::new_unique()))
+            .is_ok());
+    }
+}
@@ -0,0 +1,36 @@
+use super::{ReserveInfo, StrategyAccounts, YieldStrategy};
+use crate::errors::ErrorCode;
+use anchor_lang::prelude::*;
+
+/// lotteries that never leave the vault, every lending instruction is refused
+pub struct NoStrategy;
+
+impl YieldStrategy for NoStrategy {
+    fn validate(_accounts: &StrategyAccounts) -> Result<()> {
+        Err(error!(ErrorCode::StrategyDisabled))
+    }
+
+    fn reserve_info(_lending_program: &Pubkey, _reserve: &AccountInfo) -> Result<ReserveInfo> {
+        Err(error!(ErrorCode::StrategyDisabled))
+    }
+
+    fn refresh(_accounts: &StrategyAccounts) -> Result<()> {
+        Err(error!(ErrorCode::StrategyDisabled))
+    }
+
+    fn deposit(
+        _accounts: &StrategyAccounts,
+        _liquidity_amount: u64,
+        _signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        Err(error!(ErrorCode::StrategyDisabled))
+    }
+
+    fn redeem(
+        _accounts: &StrategyAccounts,
+        _collateral_amount: u64,
+        _signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        Err(error!(ErrorCode::StrategyDisabled))
+    }
+}
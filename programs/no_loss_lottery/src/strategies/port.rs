@@ -0,0 +1,141 @@
+use super::{read_pubkey, read_u64, read_wad, ReserveInfo, StrategyAccounts, YieldStrategy};
+use crate::errors::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    program::{invoke, invoke_signed},
+    program_option::COption,
+};
+use port_variable_rate_lending_instructions::instruction::{
+    deposit_reserve_liquidity, redeem_reserve_collateral, refresh_reserve,
+};
+
+// offsets into Port's packed Reserve
+const LENDING_MARKET_OFFSET: usize = 10;
+const LIQUIDITY_MINT_OFFSET: usize = 42;
+const LIQUIDITY_SUPPLY_OFFSET: usize = 75;
+// COption<Pubkey>, u32 tag followed by the key
+const LIQUIDITY_ORACLE_OFFSET: usize = 107;
+const AVAILABLE_AMOUNT_OFFSET: usize = 143;
+const BORROWED_AMOUNT_WADS_OFFSET: usize = 151;
+const COLLATERAL_MINT_OFFSET: usize = 199;
+const COLLATERAL_SUPPLY_OFFSET: usize = 231;
+
+/// Port Finance variable rate lending
+pub struct PortStrategy;
+
+impl YieldStrategy for PortStrategy {
+    // the program id itself is checked against the config allow list,
+    // Port runs under different ids on devnet and mainnet
+    fn validate(accounts: &StrategyAccounts) -> Result<()> {
+        require!(
+            accounts.reserve.owner == accounts.lending_program.key
+                && accounts.lending_market.owner == accounts.lending_program.key,
+            ErrorCode::InvalidStrategyAccounts,
+        );
+        Ok(())
+    }
+
+    fn reserve_info(_lending_program: &Pubkey, reserve: &AccountInfo) -> Result<ReserveInfo> {
+        let data = reserve.try_borrow_data()?;
+        let oracle_tag = data
+            .get(LIQUIDITY_ORACLE_OFFSET..LIQUIDITY_ORACLE_OFFSET + 4)
+            .ok_or_else(|| error!(ErrorCode::InvalidStrategyAccounts))?;
+        let liquidity_oracle = if oracle_tag == [1, 0, 0, 0] {
+            Some(read_pubkey(&data, LIQUIDITY_ORACLE_OFFSET + 4)?)
+        } else {
+            None
+        };
+        Ok(ReserveInfo {
+            lending_market: read_pubkey(&data, LENDING_MARKET_OFFSET)?,
+            liquidity_mint: read_pubkey(&data, LIQUIDITY_MINT_OFFSET)?,
+            liquidity_supply: read_pubkey(&data, LIQUIDITY_SUPPLY_OFFSET)?,
+            liquidity_oracle,
+            collateral_mint: read_pubkey(&data, COLLATERAL_MINT_OFFSET)?,
+            total_liquidity: read_u64(&data, AVAILABLE_AMOUNT_OFFSET)?
+                + read_wad(&data, BORROWED_AMOUNT_WADS_OFFSET)?,
+            available_liquidity: read_u64(&data, AVAILABLE_AMOUNT_OFFSET)?,
+            collateral_supply: read_u64(&data, COLLATERAL_SUPPLY_OFFSET)?,
+        })
+    }
+
+    fn refresh(accounts: &StrategyAccounts) -> Result<()> {
+        let refresh_ix = refresh_reserve(
+            accounts.lending_program.key(),
+            accounts.reserve.key(),
+            COption::Some(accounts.reserve_liquidity_oracle.key()),
+        );
+        let mut account_infos = vec![
+            accounts.lending_program.clone(),
+            accounts.reserve.clone(),
+            accounts.reserve_liquidity_oracle.clone(),
+            accounts.clock.clone(),
+        ];
+        account_infos.extend_from_slice(accounts.remaining_accounts);
+        invoke(&refresh_ix, &account_infos)?;
+        Ok(())
+    }
+
+    fn deposit(
+        accounts: &StrategyAccounts,
+        liquidity_amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let deposit_ix = deposit_reserve_liquidity(
+            accounts.lending_program.key(),
+            liquidity_amount,
+            accounts.source_liquidity_vault.key(),
+            accounts.destination_collateral_vault.key(),
+            accounts.reserve.key(),
+            accounts.reserve_liquidity_supply.key(),
+            accounts.reserve_collateral_mint.key(),
+            accounts.lending_market.key(),
+            accounts.authority.key(),
+        );
+        let mut account_infos = vec![
+            accounts.lending_program.clone(),
+            accounts.source_liquidity_vault.clone(),
+            accounts.destination_collateral_vault.clone(),
+            accounts.reserve.clone(),
+            accounts.reserve_liquidity_supply.clone(),
+            accounts.reserve_collateral_mint.clone(),
+            accounts.lending_market.clone(),
+            accounts.authority.clone(),
+            accounts.clock.clone(),
+        ];
+        account_infos.extend_from_slice(accounts.remaining_accounts);
+        invoke_signed(&deposit_ix, &account_infos, signer_seeds)?;
+        Ok(())
+    }
+
+    fn redeem(
+        accounts: &StrategyAccounts,
+        collateral_amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let redeem_ix = redeem_reserve_collateral(
+            accounts.lending_program.key(),
+            collateral_amount,
+            accounts.destination_collateral_vault.key(),
+            accounts.source_liquidity_vault.key(),
+            accounts.reserve.key(),
+            accounts.reserve_collateral_mint.key(),
+            accounts.reserve_liquidity_supply.key(),
+            accounts.lending_market.key(),
+            accounts.authority.key(),
+        );
+        let mut account_infos = vec![
+            accounts.lending_program.clone(),
+            accounts.destination_collateral_vault.clone(),
+            accounts.source_liquidity_vault.clone(),
+            accounts.reserve.clone(),
+            accounts.reserve_collateral_mint.clone(),
+            accounts.reserve_liquidity_supply.clone(),
+            accounts.lending_market.clone(),
+            accounts.authority.clone(),
+            accounts.clock.clone(),
+        ];
+        account_infos.extend_from_slice(accounts.remaining_accounts);
+        invoke_signed(&redeem_ix, &account_infos, signer_seeds)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,381 @@
+use super::{read_pubkey, PortStrategy, YieldStrategy};
+use crate::errors::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::{invoke, invoke_signed},
+    program_option::COption,
+    sysvar,
+};
+use anchor_spl::token::spl_token;
+use port_variable_rate_lending_instructions::instruction::refresh_reserve;
+
+// Port lending instruction tags
+const INIT_OBLIGATION: u8 = 6;
+const REFRESH_OBLIGATION: u8 = 7;
+const DEPOSIT_OBLIGATION_COLLATERAL: u8 = 8;
+const WITHDRAW_OBLIGATION_COLLATERAL: u8 = 9;
+
+// Port staking instruction tags
+const CREATE_STAKE_ACCOUNT: u8 = 1;
+const CLAIM_REWARD: u8 = 4;
+
+// collateral supply the obligation deposits land in, after the collateral mint and its supply
+const COLLATERAL_SUPPLY_PUBKEY_OFFSET: usize = 239;
+
+/// Port staking, collateral deposited into an obligation owned by the vault signer
+/// is staked in the reserve's staking pool and earns the pool's reward token.
+/// every call here is signed by the vault signer
+pub struct PortStakingAccounts<'info> {
+    pub lending_program: AccountInfo<'info>,
+    pub staking_program: AccountInfo<'info>,
+    pub reserve: AccountInfo<'info>,
+    pub reserve_liquidity_oracle: AccountInfo<'info>,
+    pub reserve_collateral_supply: AccountInfo<'info>,
+    pub lending_market: AccountInfo<'info>,
+    pub lending_market_authority: AccountInfo<'info>,
+    pub obligation: AccountInfo<'info>,
+    pub stake_account: AccountInfo<'info>,
+    pub staking_pool: AccountInfo<'info>,
+    pub collateral_vault: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub clock: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+}
+
+/// the reserve account obligation deposits of its collateral are held in
+pub fn reserve_collateral_supply(reserve: &AccountInfo) -> Result<Pubkey> {
+    read_pubkey(&reserve.try_borrow_data()?, COLLATERAL_SUPPLY_PUBKEY_OFFSET)
+}
+
+/// the oracle and collateral supply passed next to `reserve` are the ones it records
+pub fn check_reserve_accounts(
+    lending_program: &Pubkey,
+    reserve: &AccountInfo,
+    liquidity_oracle: &Pubkey,
+    collateral_supply: &Pubkey,
+) -> Result<()> {
+    require!(
+        reserve.owner == lending_program,
+        ErrorCode::InvalidStakingAccounts,
+    );
+    let reserve_info = PortStrategy::reserve_info(lending_program, reserve)?;
+    require!(
+        reserve_info
+            .liquidity_oracle
+            .map_or(true, |oracle| oracle == *liquidity_oracle),
+        ErrorCode::InvalidStakingAccounts,
+    );
+    require!(
+        reserve_collateral_supply(reserve)? == *collateral_supply,
+        ErrorCode::InvalidStakingAccounts,
+    );
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn init_obligation<'info>(
+    lending_program: &AccountInfo<'info>,
+    obligation: &AccountInfo<'info>,
+    lending_market: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    clock: &AccountInfo<'info>,
+    rent: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let init_ix = Instruction {
+        program_id: lending_program.key(),
+        accounts: vec![
+            AccountMeta::new(obligation.key(), false),
+            AccountMeta::new_readonly(lending_market.key(), false),
+            AccountMeta::new_readonly(authority.key(), true),
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ],
+        data: vec![INIT_OBLIGATION],
+    };
+    invoke_signed(
+        &init_ix,
+        &[
+            lending_program.clone(),
+            obligation.clone(),
+            lending_market.clone(),
+            authority.clone(),
+            clock.clone(),
+            rent.clone(),
+            token_program.clone(),
+        ],
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+pub fn create_stake_account<'info>(
+    staking_program: &AccountInfo<'info>,
+    stake_account: &AccountInfo<'info>,
+    staking_pool: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    rent: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let create_ix = Instruction {
+        program_id: staking_program.key(),
+        accounts: vec![
+            AccountMeta::new(stake_account.key(), false),
+            AccountMeta::new_readonly(staking_pool.key(), false),
+            AccountMeta::new_readonly(authority.key(), true),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+        data: vec![CREATE_STAKE_ACCOUNT],
+    };
+    invoke_signed(
+        &create_ix,
+        &[
+            staking_program.clone(),
+            stake_account.clone(),
+            staking_pool.clone(),
+            authority.clone(),
+            rent.clone(),
+        ],
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+impl<'info> PortStakingAccounts<'info> {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.obligation.owner == self.lending_program.key
+                && self.stake_account.owner == self.staking_program.key
+                && self.staking_pool.owner == self.staking_program.key,
+            ErrorCode::InvalidStakingAccounts,
+        );
+        check_reserve_accounts(
+            self.lending_program.key,
+            &self.reserve,
+            self.reserve_liquidity_oracle.key,
+            self.reserve_collateral_supply.key,
+        )
+    }
+
+    /// obligation deposits and withdrawals need the reserve and obligation
+    /// refreshed in the same slot
+    pub fn refresh(&self) -> Result<()> {
+        let refresh_ix = refresh_reserve(
+            self.lending_program.key(),
+            self.reserve.key(),
+            COption::Some(self.reserve_liquidity_oracle.key()),
+        );
+        invoke(
+            &refresh_ix,
+            &[
+                self.lending_program.clone(),
+                self.reserve.clone(),
+                self.reserve_liquidity_oracle.clone(),
+                self.clock.clone(),
+            ],
+        )?;
+
+        let refresh_ix = Instruction {
+            program_id: self.lending_program.key(),
+            accounts: vec![
+                AccountMeta::new(self.obligation.key(), false),
+                AccountMeta::new_readonly(sysvar::clock::ID, false),
+                AccountMeta::new_readonly(self.reserve.key(), false),
+            ],
+            data: vec![REFRESH_OBLIGATION],
+        };
+        invoke(
+            &refresh_ix,
+            &[
+                self.lending_program.clone(),
+                self.obligation.clone(),
+                self.clock.clone(),
+                self.reserve.clone(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// moves collateral from the vault into the obligation, which stakes it
+    pub fn stake(&self, collateral_amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        let stake_ix = Instruction {
+            program_id: self.lending_program.key(),
+            accounts: vec![
+                AccountMeta::new(self.collateral_vault.key(), false),
+                AccountMeta::new(self.reserve_collateral_supply.key(), false),
+                AccountMeta::new_readonly(self.reserve.key(), false),
+                AccountMeta::new(self.obligation.key(), false),
+                AccountMeta::new_readonly(self.lending_market.key(), false),
+                AccountMeta::new_readonly(self.authority.key(), true),
+                AccountMeta::new_readonly(self.authority.key(), true),
+                AccountMeta::new_readonly(sysvar::clock::ID, false),
+                AccountMeta::new_readonly(spl_token::ID, false),
+                AccountMeta::new(self.stake_account.key(), false),
+                AccountMeta::new(self.staking_pool.key(), false),
+                AccountMeta::new_readonly(self.staking_program.key(), false),
+            ],
+            data: amount_data(DEPOSIT_OBLIGATION_COLLATERAL, collateral_amount),
+        };
+        invoke_signed(
+            &stake_ix,
+            &[
+                self.lending_program.clone(),
+                self.collateral_vault.clone(),
+                self.reserve_collateral_supply.clone(),
+                self.reserve.clone(),
+                self.obligation.clone(),
+                self.lending_market.clone(),
+                self.authority.clone(),
+                self.clock.clone(),
+                self.token_program.clone(),
+                self.stake_account.clone(),
+                self.staking_pool.clone(),
+                self.staking_program.clone(),
+            ],
+            signer_seeds,
+        )?;
+        Ok(())
+    }
+
+    /// moves collateral out of the obligation back into the vault, unstaking it
+    pub fn unstake(&self, collateral_amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        let unstake_ix = Instruction {
+            program_id: self.lending_program.key(),
+            accounts: vec![
+                AccountMeta::new(self.reserve_collateral_supply.key(), false),
+                AccountMeta::new(self.collateral_vault.key(), false),
+                AccountMeta::new_readonly(self.reserve.key(), false),
+                AccountMeta::new(self.obligation.key(), false),
+                AccountMeta::new_readonly(self.lending_market.key(), false),
+                AccountMeta::new_readonly(self.lending_market_authority.key(), false),
+                AccountMeta::new_readonly(self.authority.key(), true),
+                AccountMeta::new_readonly(sysvar::clock::ID, false),
+                AccountMeta::new_readonly(spl_token::ID, false),
+                AccountMeta::new(self.stake_account.key(), false),
+                AccountMeta::new(self.staking_pool.key(), false),
+                AccountMeta::new_readonly(self.staking_program.key(), false),
+            ],
+            data: amount_data(WITHDRAW_OBLIGATION_COLLATERAL, collateral_amount),
+        };
+        invoke_signed(
+            &unstake_ix,
+            &[
+                self.lending_program.clone(),
+                self.reserve_collateral_supply.clone(),
+                self.collateral_vault.clone(),
+                self.reserve.clone(),
+                self.obligation.clone(),
+                self.lending_market.clone(),
+                self.lending_market_authority.clone(),
+                self.authority.clone(),
+                self.clock.clone(),
+                self.token_program.clone(),
+                self.stake_account.clone(),
+                self.staking_pool.clone(),
+                self.staking_program.clone(),
+            ],
+            signer_seeds,
+        )?;
+        Ok(())
+    }
+}
+
+/// collects what the stake account earned into `reward_dest`
+#[allow(clippy::too_many_arguments)]
+pub fn claim_reward<'info>(
+    staking_program: &AccountInfo<'info>,
+    stake_account: &AccountInfo<'info>,
+    staking_pool: &AccountInfo<'info>,
+    reward_token_pool: &AccountInfo<'info>,
+    reward_dest: &AccountInfo<'info>,
+    staking_program_authority: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    clock: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let claim_ix = Instruction {
+        program_id: staking_program.key(),
+        accounts: vec![
+            AccountMeta::new_readonly(authority.key(), true),
+            AccountMeta::new(stake_account.key(), false),
+            AccountMeta::new(staking_pool.key(), false),
+            AccountMeta::new(reward_token_pool.key(), false),
+            AccountMeta::new(reward_dest.key(), false),
+            AccountMeta::new_readonly(staking_program_authority.key(), false),
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ],
+        data: vec![CLAIM_REWARD],
+    };
+    invoke_signed(
+        &claim_ix,
+        &[
+            staking_program.clone(),
+            authority.clone(),
+            stake_account.clone(),
+            staking_pool.clone(),
+            reward_token_pool.clone(),
+            reward_dest.clone(),
+            staking_program_authority.clone(),
+            clock.clone(),
+            token_program.clone(),
+        ],
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+fn amount_data(tag: u8, amount: u64) -> Vec<u8> {
+    let mut data = Vec::with_capacity(9);
+    data.push(tag);
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // oracle tag and key, then the collateral supply key
+    fn reserve_data(oracle: Option<Pubkey>, collateral_supply: Pubkey) -> Vec<u8> {
+        let mut data = vec![0u8; 300];
+        if let Some(oracle) = oracle {
+            data[107..111].copy_from_slice(&[1, 0, 0, 0]);
+            data[111..143].copy_from_slice(oracle.as_ref());
+        }
+        data[COLLATERAL_SUPPLY_PUBKEY_OFFSET..COLLATERAL_SUPPLY_PUBKEY_OFFSET + 32]
+            .copy_from_slice(collateral_supply.as_ref());
+        data
+    }
+
+    fn check(data: &mut [u8], lending_owned: bool, oracle: &Pubkey, supply: &Pubkey) -> Result<()> {
+        let key = Pubkey::new_unique();
+        let lending_program = Pubkey::new_unique();
+        let other_program = Pubkey::new_unique();
+        let owner = if lending_owned { &lending_program } else { &other_program };
+        let mut lamports = 0;
+        let reserve = AccountInfo::new(&key, false, false, &mut lamports, data, owner, false, 0);
+        check_reserve_accounts(&lending_program, &reserve, oracle, supply)
+    }
+
+    #[test]
+    fn staking_accounts_match_the_reserve() {
+        let (oracle, supply) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = reserve_data(Some(oracle), supply);
+        assert!(check(&mut data, true, &oracle, &supply).is_ok());
+        assert!(check(&mut data, true, &Pubkey::new_unique(), &supply).is_err());
+        assert!(check(&mut data, true, &oracle, &Pubkey::new_unique()).is_err());
+        assert!(check(&mut data, false, &oracle, &supply).is_err());
+    }
+
+    #[test]
+    fn reserve_without_an_oracle_takes_any() {
+        let supply = Pubkey::new_unique();
+        let mut data = reserve_data(None, supply);
+        assert!(check(&mut data, true, &Pubkey::new_unique(), &supply).is_ok());
+    }
+}
@@ -0,0 +1,209 @@
+use super::{read_pubkey, read_u64, read_wad, ReserveInfo, StrategyAccounts, YieldStrategy};
+use crate::errors::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::{invoke, invoke_signed},
+    sysvar,
+};
+use anchor_spl::token::spl_token;
+
+// Solend token-lending instruction tags
+const REFRESH_RESERVE: u8 = 3;
+const DEPOSIT_RESERVE_LIQUIDITY: u8 = 4;
+const REDEEM_RESERVE_COLLATERAL: u8 = 5;
+
+// offsets into Solend's packed Reserve
+const LENDING_MARKET_OFFSET: usize = 10;
+const LIQUIDITY_MINT_OFFSET: usize = 42;
+const LIQUIDITY_SUPPLY_OFFSET: usize = 75;
+const PYTH_ORACLE_OFFSET: usize = 107;
+const SWITCHBOARD_ORACLE_OFFSET: usize = 139;
+const AVAILABLE_AMOUNT_OFFSET: usize = 171;
+const BORROWED_AMOUNT_WADS_OFFSET: usize = 179;
+const COLLATERAL_MINT_OFFSET: usize = 227;
+const COLLATERAL_SUPPLY_OFFSET: usize = 259;
+
+/// Solend token lending, expects
+/// `[lending_market_authority, switchboard_oracle, token_program, ..]` in remaining accounts,
+/// `reserve_liquidity_oracle` is the reserve's pyth oracle
+pub struct SolendStrategy;
+
+impl SolendStrategy {
+    fn lending_market_authority<'a, 'info>(
+        accounts: &'a StrategyAccounts<'_, 'info>,
+    ) -> &'a AccountInfo<'info> {
+        &accounts.remaining_accounts[0]
+    }
+
+    fn switchboard_oracle<'a, 'info>(
+        accounts: &'a StrategyAccounts<'_, 'info>,
+    ) -> &'a AccountInfo<'info> {
+        &accounts.remaining_accounts[1]
+    }
+
+    fn token_program<'a, 'info>(accounts: &'a StrategyAccounts<'_, 'info>) -> &'a AccountInfo<'info> {
+        &accounts.remaining_accounts[2]
+    }
+
+    fn amount_data(tag: u8, amount: u64) -> Vec<u8> {
+        let mut data = Vec::with_capacity(9);
+        data.push(tag);
+        data.extend_from_slice(&amount.to_le_bytes());
+        data
+    }
+}
+
+impl YieldStrategy for SolendStrategy {
+    fn validate(accounts: &StrategyAccounts) -> Result<()> {
+        require!(
+            accounts.remaining_accounts.len() >= 3,
+            ErrorCode::InvalidStrategyAccounts,
+        );
+        require!(
+            accounts.reserve.owner == accounts.lending_program.key
+                && accounts.lending_market.owner == accounts.lending_program.key,
+            ErrorCode::InvalidStrategyAccounts,
+        );
+        let (lending_market_authority, _) = Pubkey::find_program_address(
+            &[accounts.lending_market.key.as_ref()],
+            accounts.lending_program.key,
+        );
+        require!(
+            *Self::lending_market_authority(accounts).key == lending_market_authority,
+            ErrorCode::InvalidStrategyAccounts,
+        );
+        require!(
+            *Self::token_program(accounts).key == spl_token::ID,
+            ErrorCode::InvalidStrategyAccounts,
+        );
+        let switchboard_oracle =
+            read_pubkey(&accounts.reserve.try_borrow_data()?, SWITCHBOARD_ORACLE_OFFSET)?;
+        require!(
+            *Self::switchboard_oracle(accounts).key == switchboard_oracle,
+            ErrorCode::InvalidStrategyAccounts,
+        );
+        Ok(())
+    }
+
+    fn reserve_info(_lending_program: &Pubkey, reserve: &AccountInfo) -> Result<ReserveInfo> {
+        let data = reserve.try_borrow_data()?;
+        Ok(ReserveInfo {
+            lending_market: read_pubkey(&data, LENDING_MARKET_OFFSET)?,
+            liquidity_mint: read_pubkey(&data, LIQUIDITY_MINT_OFFSET)?,
+            liquidity_supply: read_pubkey(&data, LIQUIDITY_SUPPLY_OFFSET)?,
+            liquidity_oracle: Some(read_pubkey(&data, PYTH_ORACLE_OFFSET)?),
+            collateral_mint: read_pubkey(&data, COLLATERAL_MINT_OFFSET)?,
+            total_liquidity: read_u64(&data, AVAILABLE_AMOUNT_OFFSET)?
+                + read_wad(&data, BORROWED_AMOUNT_WADS_OFFSET)?,
+            available_liquidity: read_u64(&data, AVAILABLE_AMOUNT_OFFSET)?,
+            collateral_supply: read_u64(&data, COLLATERAL_SUPPLY_OFFSET)?,
+        })
+    }
+
+    fn refresh(accounts: &StrategyAccounts) -> Result<()> {
+        let refresh_ix = Instruction {
+            program_id: accounts.lending_program.key(),
+            accounts: vec![
+                AccountMeta::new(accounts.reserve.key(), false),
+                AccountMeta::new_readonly(accounts.reserve_liquidity_oracle.key(), false),
+                AccountMeta::new_readonly(Self::switchboard_oracle(accounts).key(), false),
+                AccountMeta::new_readonly(sysvar::clock::ID, false),
+            ],
+            data: vec![REFRESH_RESERVE],
+        };
+        invoke(
+            &refresh_ix,
+            &[
+                accounts.lending_program.clone(),
+                accounts.reserve.clone(),
+                accounts.reserve_liquidity_oracle.clone(),
+                Self::switchboard_oracle(accounts).clone(),
+                accounts.clock.clone(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn deposit(
+        accounts: &StrategyAccounts,
+        liquidity_amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let deposit_ix = Instruction {
+            program_id: accounts.lending_program.key(),
+            accounts: vec![
+                AccountMeta::new(accounts.source_liquidity_vault.key(), false),
+                AccountMeta::new(accounts.destination_collateral_vault.key(), false),
+                AccountMeta::new(accounts.reserve.key(), false),
+                AccountMeta::new(accounts.reserve_liquidity_supply.key(), false),
+                AccountMeta::new(accounts.reserve_collateral_mint.key(), false),
+                AccountMeta::new_readonly(accounts.lending_market.key(), false),
+                AccountMeta::new_readonly(Self::lending_market_authority(accounts).key(), false),
+                AccountMeta::new_readonly(accounts.authority.key(), true),
+                AccountMeta::new_readonly(sysvar::clock::ID, false),
+                AccountMeta::new_readonly(Self::token_program(accounts).key(), false),
+            ],
+            data: Self::amount_data(DEPOSIT_RESERVE_LIQUIDITY, liquidity_amount),
+        };
+        invoke_signed(
+            &deposit_ix,
+            &[
+                accounts.lending_program.clone(),
+                accounts.source_liquidity_vault.clone(),
+                accounts.destination_collateral_vault.clone(),
+                accounts.reserve.clone(),
+                accounts.reserve_liquidity_supply.clone(),
+                accounts.reserve_collateral_mint.clone(),
+                accounts.lending_market.clone(),
+                Self::lending_market_authority(accounts).clone(),
+                accounts.authority.clone(),
+                accounts.clock.clone(),
+                Self::token_program(accounts).clone(),
+            ],
+            signer_seeds,
+        )?;
+        Ok(())
+    }
+
+    fn redeem(
+        accounts: &StrategyAccounts,
+        collateral_amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let redeem_ix = Instruction {
+            program_id: accounts.lending_program.key(),
+            accounts: vec![
+                AccountMeta::new(accounts.destination_collateral_vault.key(), false),
+                AccountMeta::new(accounts.source_liquidity_vault.key(), false),
+                AccountMeta::new(accounts.reserve.key(), false),
+                AccountMeta::new(accounts.reserve_collateral_mint.key(), false),
+                AccountMeta::new(accounts.reserve_liquidity_supply.key(), false),
+                AccountMeta::new_readonly(accounts.lending_market.key(), false),
+                AccountMeta::new_readonly(Self::lending_market_authority(accounts).key(), false),
+                AccountMeta::new_readonly(accounts.authority.key(), true),
+                AccountMeta::new_readonly(sysvar::clock::ID, false),
+                AccountMeta::new_readonly(Self::token_program(accounts).key(), false),
+            ],
+            data: Self::amount_data(REDEEM_RESERVE_COLLATERAL, collateral_amount),
+        };
+        invoke_signed(
+            &redeem_ix,
+            &[
+                accounts.lending_program.clone(),
+                accounts.destination_collateral_vault.clone(),
+                accounts.source_liquidity_vault.clone(),
+                accounts.reserve.clone(),
+                accounts.reserve_collateral_mint.clone(),
+                accounts.reserve_liquidity_supply.clone(),
+                accounts.lending_market.clone(),
+                Self::lending_market_authority(accounts).clone(),
+                accounts.authority.clone(),
+                accounts.clock.clone(),
+                Self::token_program(accounts).clone(),
+            ],
+            signer_seeds,
+        )?;
+        Ok(())
+    }
+}
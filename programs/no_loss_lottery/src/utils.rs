@@ -0,0 +1,18 @@
+pub const VAULT_SIGNER_STR: &str = "vault_signer";
+pub const LOTTERY_INFO_STR: &str = "lottery_info";
+pub const PRIZE_VAULT_STR: &str = "prize_vault";
+pub const UNWRAP_STR: &str = "unwrap";
+pub const CONFIG_STR: &str = "config";
+pub const QUEUED_CHANGE_STR: &str = "queued_change";
+pub const MULTISIG_STR: &str = "multisig";
+pub const PROPOSAL_STR: &str = "proposal";
+pub const STAKING_INFO_STR: &str = "staking_info";
+pub const REWARDS_VAULT_STR: &str = "rewards_vault";
+pub const WITHDRAWAL_QUEUE_STR: &str = "withdrawal_queue";
+
+pub const STATE_SEED: &[u8] = b"STATE";
+
+pub const MAX_RESULT: u64 = u64::MAX;
+
+// fees are expressed in basis points of the prize
+pub const MAX_BPS: u64 = 10_000;
@@ -1,7 +1,8 @@
-import * as anchor from "@project-serum/anchor";
-import { Program, } from "@project-serum/anchor";
+import * as anchor from "@coral-xyz/anchor";
+import { Program, } from "@coral-xyz/anchor";
 import { NoLossLottery } from "../target/types/no_loss_lottery";
 import {
+  deriveConfigPDA,
   deriveLotteryInfoPDA,
   deriveVaultSignerPDA,
   sleep,
@@ -61,6 +62,7 @@ describe("no_loss_lottery", () => {
   let sourceLiquidityMintInfo: RawMint;
   let destinationCollateralMintInfo: RawMint;
   let lotteryPDA: anchor.web3.PublicKey;
+  let configPDA: anchor.web3.PublicKey;
 
   // Switchboard VRF
   let switchboard: SwitchboardTestContext;
@@ -97,6 +99,7 @@ describe("no_loss_lottery", () => {
     destinationCollateralMintInfo = await mintInfo(provider, WSOL_PTOKEN_MINT);
 
     lotteryPDA = (await deriveLotteryInfoPDA(programId))[0]
+    configPDA = (await deriveConfigPDA(programId))[0]
     //generate users and send lamports to them
     for (let i = 0; i < 5; i++) {
       const user = anchor.web3.Keypair.generate()
@@ -170,6 +173,39 @@ describe("no_loss_lottery", () => {
   let vrfAccount: VrfAccount;
   let permissionAccount: PermissionAccount;
 
+  it("initializes program config", async () => {
+    const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+      [programId.toBuffer()],
+      new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+    // the config is a singleton, an earlier run on the same cluster already created it
+    if ((await program.account.programConfig.fetchNullable(configPDA)) === null) {
+      await program.methods
+        .initializeConfig({
+          guardian: provider.wallet.publicKey,
+          treasury: provider.wallet.publicKey,
+          feeBps: 0,
+          allowedLendingPrograms: [lendingProgram],
+          allowedReserves: [SOL_RESERVE],
+          allowedOracleQueues: [switchboard.queue.publicKey],
+          timelockDelay: new anchor.BN(0),
+          allowedStakingPrograms: [],
+        })
+        .accounts({
+          config: configPDA,
+          program: programId,
+          programData,
+          admin: provider.wallet.publicKey,
+        })
+        .rpc()
+    }
+    const config = await program.account.programConfig.fetch(configPDA)
+    assert.ok(config.admin.equals(provider.wallet.publicKey))
+    assert.ok(config.allowedLendingPrograms.some(key => key.equals(lendingProgram)))
+    assert.ok(config.allowedReserves.some(key => key.equals(SOL_RESERVE)))
+    assert.ok(config.allowedOracleQueues.some(key => key.equals(switchboard.queue.publicKey)))
+  })
+
   it("initializes Lottery and VRF accounts", async () => {
 
 
@@ -230,11 +266,19 @@ describe("no_loss_lottery", () => {
       await program.methods
         .initializeLottery({
           entryFee: new anchor.BN(LOTTERY_ENTRY_FEE),
-          maxParticipants: new anchor.BN(users.length)
+          maxParticipants: new anchor.BN(users.length),
+          operator: provider.wallet.publicKey,
+          guardian: provider.wallet.publicKey,
+          // Port
+          strategy: 0,
+          investOnEntry: false,
+          bufferBps: 0,
         })
         .accounts({
           state: vrfClientKey,
           vrf: vrfAccount.publicKey,
+          lendingProgram,
+          reserve: SOL_RESERVE,
           sourceLiquidityMint: NATIVE_MINT,
           destinationCollateralMint: WSOL_PTOKEN_MINT,
           sourceLiquidityVault,
@@ -243,6 +287,9 @@ describe("no_loss_lottery", () => {
           authority: provider.wallet.publicKey,
           vaultSigner,
           lotteryAcct: lotteryPDA,
+          config: configPDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          collateralTokenProgram: TOKEN_PROGRAM_ID,
         })
         .rpc()
     } catch (error) {
@@ -272,13 +319,12 @@ describe("no_loss_lottery", () => {
             .enterLottery()
             .accounts({
               sourceLiquidityMint: NATIVE_MINT,
-              destinationCollateralMint: WSOL_PTOKEN_MINT,
               userTokenAccount,
               sourceLiquidityVault,
-              destinationCollateralVault,
               user: user.publicKey,
               vaultSigner,
               lotteryAcct: lotteryPDA,
+              config: configPDA,
               systemProgram: anchor.web3.SystemProgram.programId,
               rent: anchor.web3.SYSVAR_RENT_PUBKEY,
               associatedTokenProgram: serumAssoToken.ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -305,20 +351,25 @@ describe("no_loss_lottery", () => {
       [sourceLiquidityMintInfo.decimals, destinationCollateralMintInfo.decimals],
       async () => {
         await program.methods
-          .deposit(vaultSignerBump)
+          .deposit(0)
           .accounts({
-            lendingProgram: lendingProgram,
-            sourceLiquidityVault,
-            destinationCollateralVault,
-            reserve: SOL_RESERVE,
-            reserveLiquiditySupply: SOL_RESERVE_LIQUIDITY_SUPPLY,
-            reserveCollateralMint: WSOL_PTOKEN_MINT,
+            lotteryAcct: lotteryPDA,
+            config: configPDA,
+            operator: provider.wallet.publicKey,
             sourceLiquidityMint: NATIVE_MINT,
-            lendingMarket: lendingMarket,
-            destinationCollateralMint: WSOL_PTOKEN_MINT,
+            sourceLiquidityVault,
             authority: vaultSigner,
-            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
-            reserveLiquidityOracle: SOL_ORACLE
+            lending: {
+              lendingProgram: lendingProgram,
+              destinationCollateralMint: WSOL_PTOKEN_MINT,
+              destinationCollateralVault,
+              reserve: SOL_RESERVE,
+              reserveLiquiditySupply: SOL_RESERVE_LIQUIDITY_SUPPLY,
+              reserveCollateralMint: WSOL_PTOKEN_MINT,
+              reserveLiquidityOracle: SOL_ORACLE,
+              lendingMarket: lendingMarket,
+              clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+            },
           })
           .remainingAccounts([
             {
@@ -384,27 +435,11 @@ describe("no_loss_lottery", () => {
         recentBlockhashes: SYSVAR_RECENT_BLOCKHASHES_PUBKEY,
         programState: programStateAccount.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
+        config: configPDA,
       })
       .rpc();
   })
 
-  it("chooses winner after vrf has been fulfilled", async () => {
-    await sleep(30000); //30 secs
-    await program.methods
-      .chooseWinner()
-      .accounts({
-        lotteryAcct: lotteryPDA,
-        state: vrfClientKey,
-        vrf: vrfAccount.publicKey
-      })
-      .rpc()
-      .catch(error => console.log(error));
-    const lotteryState = await program.account.lotteryInfo.fetch(lotteryPDA)
-    lotteryWinner = lotteryState.winner;
-    console.log("And the winner is: ", lotteryState.winner.toBase58());
-
-  })
-
   it("withdraws tokens from lending pool", async () => {
 
 
@@ -414,20 +449,25 @@ describe("no_loss_lottery", () => {
       [sourceLiquidityMintInfo.decimals, destinationCollateralMintInfo.decimals],
       async () => {
         await program.methods
-          .withdraw(vaultSignerBump)
+          .withdraw(0)
           .accounts({
-            lendingProgram: lendingProgram,
-            sourceLiquidityVault,
-            destinationCollateralVault,
-            reserve: SOL_RESERVE,
-            reserveLiquiditySupply: SOL_RESERVE_LIQUIDITY_SUPPLY,
-            reserveCollateralMint: WSOL_PTOKEN_MINT,
+            lotteryAcct: lotteryPDA,
+            config: configPDA,
+            operator: provider.wallet.publicKey,
             sourceLiquidityMint: NATIVE_MINT,
-            lendingMarket: lendingMarket,
-            destinationCollateralMint: WSOL_PTOKEN_MINT,
+            sourceLiquidityVault,
             authority: vaultSigner,
-            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
-            reserveLiquidityOracle: SOL_ORACLE
+            lending: {
+              lendingProgram: lendingProgram,
+              destinationCollateralMint: WSOL_PTOKEN_MINT,
+              destinationCollateralVault,
+              reserve: SOL_RESERVE,
+              reserveLiquiditySupply: SOL_RESERVE_LIQUIDITY_SUPPLY,
+              reserveCollateralMint: WSOL_PTOKEN_MINT,
+              reserveLiquidityOracle: SOL_ORACLE,
+              lendingMarket: lendingMarket,
+              clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+            },
           })
           .remainingAccounts([
             {
@@ -451,6 +491,35 @@ describe("no_loss_lottery", () => {
     assert.ok(destinationCollateralVaultDifference < 0)
   })
 
+  it("chooses winner after vrf has been fulfilled", async () => {
+    await sleep(30000); //30 secs
+    const [prizeVault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("prize_vault")],
+      programId
+    );
+    await program.methods
+      .chooseWinner()
+      .accounts({
+        lotteryAcct: lotteryPDA,
+        config: configPDA,
+        state: vrfClientKey,
+        vrf: vrfAccount.publicKey,
+        operator: provider.wallet.publicKey,
+        sourceLiquidityMint: NATIVE_MINT,
+        sourceLiquidityVault,
+        // swept into the winnings when a prize was funded or yield harvested
+        prizeVault,
+        vaultSigner,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc()
+      .catch(error => console.log(error));
+    const lotteryState = await program.account.lotteryInfo.fetch(lotteryPDA)
+    lotteryWinner = lotteryState.winner;
+    console.log("And the winner is: ", lotteryState.winner.toBase58());
+
+  })
+
   it("withdraw user tokens", async () => {
 
     for (let i = 0; i < users.length; i++) {
@@ -458,6 +527,7 @@ describe("no_loss_lottery", () => {
       const userTokenAccount = await serumAssoToken.getAssociatedTokenAddress(user.publicKey, NATIVE_MINT,)
       console.log(user.publicKey.toBase58());
       const balanceBefore = await provider.connection.getTokenAccountBalance(userTokenAccount)
+      const treasuryTokenAccount = await serumAssoToken.getAssociatedTokenAddress(provider.wallet.publicKey, NATIVE_MINT,)
 
       try {
         await program.methods
@@ -466,9 +536,11 @@ describe("no_loss_lottery", () => {
             sourceLiquidityMint: NATIVE_MINT,
             userTokenAccount,
             sourceLiquidityVault,
+            treasuryTokenAccount,
             user: user.publicKey,
             vaultSigner,
             lotteryAcct: lotteryPDA,
+            config: configPDA,
             systemProgram: anchor.web3.SystemProgram.programId,
             rent: anchor.web3.SYSVAR_RENT_PUBKEY,
             associatedTokenProgram: serumAssoToken.ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -532,13 +604,70 @@ describe("no_loss_lottery", () => {
     }
   })
   it("closes accounts", async () => {
+    const [multisigPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("multisig")],
+      programId
+    );
+    try {
+      await program.methods
+        .createMultisig([payer.publicKey], 1)
+        .accounts({
+          multisig: multisigPDA,
+          config: configPDA,
+          admin: payer.publicKey,
+        })
+        .rpc()
+    } catch (error) {
+      console.log("Multisig has already been created...",);
+    }
+    const multisig = await program.account.multisig.fetch(multisigPDA)
+    const [proposalPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("proposal"), multisig.proposalCount.toArrayLike(Buffer, "le", 8)],
+      programId
+    );
+    const [prizeVault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("prize_vault")],
+      programId
+    );
+    const [rewardsVault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("rewards_vault")],
+      programId
+    );
+    const [stakingInfo] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("staking_info")],
+      programId
+    );
+    await program.methods
+      .createProposal({ closeAccounts: {} })
+      .accounts({
+        proposal: proposalPDA,
+        multisig: multisigPDA,
+        proposer: payer.publicKey,
+      })
+      .rpc()
+
     await program.methods
       .closeAccounts()
       .accounts({
         state: vrfClientKey,
         lotteryAcct: lotteryPDA,
+        config: configPDA,
         vrf: vrfAccount.publicKey,
+        proposal: proposalPDA,
+        multisig: multisigPDA,
+        sourceLiquidityMint: NATIVE_MINT,
+        sourceLiquidityVault,
+        destinationCollateralMint: WSOL_PTOKEN_MINT,
+        destinationCollateralVault,
+        prizeVault,
+        rewardsVault,
+        stakingInfo,
+        treasuryTokenAccount: await serumAssoToken.getAssociatedTokenAddress(provider.wallet.publicKey, NATIVE_MINT,),
+        vaultSigner,
+        payer: payer.publicKey,
         user: payer.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        collateralTokenProgram: TOKEN_PROGRAM_ID,
       })
       .rpc()
       .catch(err => console.log(err));
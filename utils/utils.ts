@@ -1,4 +1,4 @@
-import * as anchor from "@project-serum/anchor";
+import * as anchor from "@coral-xyz/anchor";
 import * as serumAssoToken from "@project-serum/associated-token";
 import {
   createMintToInstruction, createSyncNativeInstruction, createWrappedNativeAccount, MintLayout, RawMint, TOKEN_PROGRAM_ID,
@@ -154,6 +154,17 @@ export async function deriveLotteryInfoPDA(
   return pda
 }
 
+export async function deriveConfigPDA(
+  programId: anchor.web3.PublicKey,
+): Promise<[anchor.web3.PublicKey, number]> {
+  const pda = await anchor.web3.PublicKey.findProgramAddress(
+    [Buffer.from("config")],
+    programId
+  );
+
+  return pda
+}
+
 export async function requestAirdrop(
   provider: anchor.AnchorProvider,
   user: anchor.web3.PublicKey,